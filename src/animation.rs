@@ -0,0 +1,718 @@
+//! Keyframed camera paths: where the viewport is, how far it is zoomed and
+//! turned, and what the fractal's parameters are at a few moments in time,
+//! with everything in between interpolated. A [`Timeline`] plays them back.
+
+use crate::{double_double::DoubleDouble, fractal::Fractal, viewport::Viewport};
+use num_complex::Complex;
+use std::{fmt, time::Duration};
+
+/// How a value eases from one keyframe into the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    /// Starts slowly and speeds up.
+    EaseIn,
+    /// Starts quickly and slows down.
+    EaseOut,
+    /// Slow at both ends.
+    EaseInOut,
+}
+
+impl Easing {
+    pub const ALL: [Easing; 4] = [
+        Easing::Linear,
+        Easing::EaseIn,
+        Easing::EaseOut,
+        Easing::EaseInOut,
+    ];
+
+    /// Maps the fraction `t` of the way through a segment, in `[0, 1]`, to
+    /// how far the values have moved.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+
+    /// The name keyframe files use.
+    pub fn name(self) -> &'static str {
+        match self {
+            Easing::Linear => "linear",
+            Easing::EaseIn => "in",
+            Easing::EaseOut => "out",
+            Easing::EaseInOut => "in-out",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Easing> {
+        Easing::ALL.into_iter().find(|easing| easing.name() == name)
+    }
+}
+
+/// The view and parameters at one moment of an animation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe {
+    /// Seconds from the start of the animation.
+    pub time: f64,
+    /// The viewport's center, to double-double precision so deep zooms can
+    /// aim between f64s.
+    pub center: (DoubleDouble, DoubleDouble),
+    /// The viewport's scale.
+    pub scale: f64,
+    /// The viewport's rotation, in radians.
+    pub rotation: f64,
+    /// The iteration or recursion depth, if the animation sets one.
+    pub depth: Option<u32>,
+    /// Values for [`Fractal::set_parameter`].
+    pub parameters: Vec<(String, f64)>,
+    /// How the segment from this keyframe to the next one eases.
+    pub easing: Easing,
+}
+
+impl Keyframe {
+    /// Shows what `viewport` shows, at `time`.
+    pub fn new(time: f64, viewport: &Viewport) -> Self {
+        Keyframe {
+            time,
+            center: viewport.precise_center(),
+            scale: viewport.scale,
+            rotation: viewport.rotation,
+            depth: None,
+            parameters: Vec::new(),
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn depth(mut self, depth: u32) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    pub fn parameter(mut self, name: &str, value: f64) -> Self {
+        match self.parameters.iter_mut().find(|(n, _)| n == name) {
+            Some((_, v)) => *v = value,
+            None => self.parameters.push((name.to_string(), value)),
+        }
+        self
+    }
+
+    /// Shows what `fractal` shows right now, at `time`, with its current
+    /// parameters, drawn at `depth`. Fractals without a viewport get the
+    /// default one, which [`Track::apply`] ignores for them.
+    pub fn capture(time: f64, depth: u32, fractal: &mut dyn Fractal) -> Self {
+        let viewport = fractal.viewport_mut().map(|viewport| *viewport);
+        let keyframe = Keyframe::new(time, &viewport.unwrap_or_default()).depth(depth);
+        fractal
+            .parameters()
+            .into_iter()
+            .fold(keyframe, |keyframe, (name, value)| {
+                keyframe.parameter(name, value)
+            })
+    }
+
+    pub fn easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// The state `amount` of the way from `self` to `next`, `amount` already
+    /// eased.
+    fn interpolate(&self, next: &Keyframe, amount: f64) -> Keyframe {
+        let lerp = |a: f64, b: f64| a + (b - a) * amount;
+
+        // Zooming at a steady rate means the scale changes by the same factor
+        // every second, not the same amount.
+        let scale = self.scale * (next.scale / self.scale).powf(amount);
+        // Move the center in step with the scale, so it covers most of the
+        // distance while the view is still wide and drifts only a few pixels
+        // once it is deep.
+        let along = if self.scale == next.scale {
+            amount
+        } else {
+            (self.scale - scale) / (self.scale - next.scale)
+        };
+        let (re, im) = self.center;
+        let center = (
+            re + (next.center.0 - re).to_f64() * along,
+            im + (next.center.1 - im).to_f64() * along,
+        );
+
+        let depth = match (self.depth, next.depth) {
+            (Some(a), Some(b)) => Some(lerp(a as f64, b as f64).round() as u32),
+            (depth, _) => depth,
+        };
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|(name, a)| {
+                let b = next.parameters.iter().find(|(n, _)| n == name);
+                (name.clone(), b.map_or(*a, |(_, b)| lerp(*a, *b)))
+            })
+            .collect();
+
+        Keyframe {
+            time: lerp(self.time, next.time),
+            center,
+            scale,
+            rotation: lerp(self.rotation, next.rotation),
+            depth,
+            parameters,
+            easing: self.easing,
+        }
+    }
+}
+
+/// A problem with one line of a keyframe file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Counting from one.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Keyframes in time order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Track {
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl Track {
+    /// Sorts `keyframes` by time.
+    pub fn new(mut keyframes: Vec<Keyframe>) -> Self {
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Track { keyframes }
+    }
+
+    /// Reads a track with one keyframe per line, as whitespace-separated
+    /// `key=value` pairs:
+    ///
+    /// ```text
+    /// # seconds, then where to look and how far zoomed in
+    /// t=0 re=-0.5 im=0 zoom=1 easing=in-out
+    /// t=8 re=-0.743643887037158704752191506114774 im=0.131825904205311970493132056385139 zoom=1e12 depth=4000
+    /// ```
+    ///
+    /// `zoom` is a magnification of `viewport`, `rotation` is in degrees and
+    /// `easing` names an [`Easing`] for the segment that follows. Any other
+    /// key has to be one of `parameters`, the fractal's
+    /// [`Fractal::parameters`]. Whatever a line leaves out carries over from
+    /// the line before, and the first line starts from `viewport` and
+    /// `parameters`. Blank lines and `#` comments are skipped.
+    pub fn parse(
+        text: &str,
+        viewport: &Viewport,
+        parameters: &[(&'static str, f64)],
+    ) -> Result<Track, ParseError> {
+        let mut keyframes: Vec<Keyframe> = Vec::new();
+        let mut previous = parameters
+            .iter()
+            .fold(Keyframe::new(0.0, viewport), |keyframe, (name, value)| {
+                keyframe.parameter(name, *value)
+            });
+
+        for (ix, line) in text.lines().enumerate() {
+            let error = |message: String| ParseError {
+                line: ix + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut keyframe = Keyframe {
+                easing: Easing::default(),
+                ..previous.clone()
+            };
+            let mut time = None;
+            for pair in line.split_whitespace() {
+                let (key, value) = pair
+                    .split_once('=')
+                    .ok_or_else(|| error(format!("expected key=value, found {pair:?}")))?;
+                let number = || {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|n| n.is_finite())
+                        .ok_or_else(|| error(format!("invalid {key} {value:?}")))
+                };
+                let precise = || {
+                    DoubleDouble::parse(value)
+                        .ok_or_else(|| error(format!("invalid {key} {value:?}")))
+                };
+                match key {
+                    "t" => time = Some(number()?),
+                    "re" => keyframe.center.0 = precise()?,
+                    "im" => keyframe.center.1 = precise()?,
+                    "zoom" => {
+                        let zoom = number()?;
+                        if zoom <= 0.0 {
+                            return Err(error(format!("zoom has to be positive, found {zoom}")));
+                        }
+                        keyframe.scale = viewport.scale / zoom;
+                    }
+                    "rotation" => keyframe.rotation = number()?.to_radians(),
+                    "depth" => {
+                        let depth = value
+                            .parse()
+                            .map_err(|_| error(format!("invalid depth {value:?}")))?;
+                        keyframe.depth = Some(depth);
+                    }
+                    "easing" => {
+                        keyframe.easing = Easing::from_name(value).ok_or_else(|| {
+                            let names: Vec<_> = Easing::ALL.iter().map(|e| e.name()).collect();
+                            error(format!(
+                                "unknown easing {value:?}, expected one of {}",
+                                names.join(", ")
+                            ))
+                        })?;
+                    }
+                    _ if parameters.iter().any(|(name, _)| *name == key) => {
+                        keyframe = keyframe.parameter(key, number()?);
+                    }
+                    _ => return Err(error(format!("unknown key {key:?}"))),
+                }
+            }
+
+            keyframe.time = time.ok_or_else(|| error("missing t".into()))?;
+            if let Some(last) = keyframes.last() {
+                if keyframe.time <= last.time {
+                    return Err(error(format!(
+                        "t={} has to come after t={}",
+                        keyframe.time, last.time
+                    )));
+                }
+            }
+            previous = keyframe.clone();
+            keyframes.push(keyframe);
+        }
+
+        if keyframes.is_empty() {
+            return Err(ParseError {
+                line: text.lines().count(),
+                message: "no keyframes".into(),
+            });
+        }
+        Ok(Track { keyframes })
+    }
+
+    /// When the last keyframe is, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// How many frames it takes to show the whole track at `fps`, counting
+    /// both the first and the last keyframe.
+    pub fn frame_count(&self, fps: f64) -> usize {
+        (self.duration() * fps + 1e-9).floor() as usize + 1
+    }
+
+    /// The state at `time`, holding the first and last keyframes before and
+    /// after the track. `None` for an empty track.
+    pub fn sample(&self, time: f64) -> Option<Keyframe> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap_or(self.keyframes.len());
+        if next == 0 || next == self.keyframes.len() {
+            let keyframe = self.keyframes.get(next.saturating_sub(1))?;
+            return Some(Keyframe {
+                time,
+                ..keyframe.clone()
+            });
+        }
+
+        let (from, to) = (&self.keyframes[next - 1], &self.keyframes[next]);
+        let amount = (time - from.time) / (to.time - from.time);
+        Some(from.interpolate(to, from.easing.apply(amount)))
+    }
+
+    /// Moves `fractal`'s viewport and sets its parameters to the state at
+    /// `time`, keeping the canvas size. Returns the depth to draw it at, if
+    /// the track sets one.
+    pub fn apply(&self, time: f64, fractal: &mut dyn Fractal) -> Option<u32> {
+        let keyframe = self.sample(time)?;
+        if let Some(viewport) = fractal.viewport_mut() {
+            let (re, im) = keyframe.center;
+            viewport.center = Complex::new(re.hi, im.hi);
+            viewport.center_low = Complex::new(re.lo, im.lo);
+            viewport.scale = keyframe.scale;
+            viewport.rotation = keyframe.rotation;
+        }
+        for (name, value) in &keyframe.parameters {
+            fractal.set_parameter(name, *value);
+        }
+        keyframe.depth
+    }
+}
+
+/// What a [`Timeline`] does when it reaches its end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Repeat {
+    /// Stops on the last frame.
+    Once,
+    /// Jumps back to the start.
+    #[default]
+    Loop,
+    /// Plays backwards to the start, then forwards again.
+    PingPong,
+}
+
+impl Repeat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Repeat::Once => "Once",
+            Repeat::Loop => "Loop",
+            Repeat::PingPong => "Ping-pong",
+        }
+    }
+
+    /// The next mode, wrapping around, for cycling through them in a UI.
+    pub fn next(&self) -> Repeat {
+        match self {
+            Repeat::Once => Repeat::Loop,
+            Repeat::Loop => Repeat::PingPong,
+            Repeat::PingPong => Repeat::Once,
+        }
+    }
+}
+
+/// A playhead moving over `duration` seconds in real time, so an animation
+/// runs at the same speed however often it is redrawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timeline {
+    /// Seconds from start to end.
+    pub duration: f64,
+    pub repeat: Repeat,
+    /// Seconds played since the start, counting every repeat.
+    elapsed: f64,
+    playing: bool,
+}
+
+impl Timeline {
+    /// A timeline at its start, playing.
+    pub fn new(duration: f64, repeat: Repeat) -> Self {
+        Timeline {
+            duration,
+            repeat,
+            elapsed: 0.0,
+            playing: true,
+        }
+    }
+
+    /// Moves the playhead on by `elapsed` wall-clock time, unless paused. A
+    /// timeline that plays [`Repeat::Once`] pauses itself at the end.
+    pub fn advance(&mut self, elapsed: Duration) {
+        if !self.playing {
+            return;
+        }
+        self.elapsed += elapsed.as_secs_f64();
+        if self.repeat == Repeat::Once && self.elapsed >= self.duration {
+            self.elapsed = self.duration;
+            self.playing = false;
+        }
+    }
+
+    /// Where the playhead is, in seconds from the start.
+    pub fn time(&self) -> f64 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        match self.repeat {
+            Repeat::Once => self.elapsed.min(self.duration),
+            Repeat::Loop => self.elapsed % self.duration,
+            Repeat::PingPong => {
+                let time = self.elapsed % (2.0 * self.duration);
+                if time > self.duration {
+                    2.0 * self.duration - time
+                } else {
+                    time
+                }
+            }
+        }
+    }
+
+    /// How far through the timeline the playhead is, from zero to one.
+    pub fn progress(&self) -> f64 {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        self.time() / self.duration
+    }
+
+    /// How many times the timeline has started over from the beginning.
+    pub fn cycle(&self) -> u64 {
+        if self.duration <= 0.0 {
+            return 0;
+        }
+        let length = match self.repeat {
+            Repeat::Once => return 0,
+            Repeat::Loop => self.duration,
+            Repeat::PingPong => 2.0 * self.duration,
+        };
+        (self.elapsed / length) as u64
+    }
+
+    /// Moves the playhead to `time` seconds from the start, playing forwards
+    /// from there.
+    pub fn seek(&mut self, time: f64) {
+        self.elapsed = time.clamp(0.0, self.duration.max(0.0));
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Whether a [`Repeat::Once`] timeline has reached its end.
+    pub fn is_finished(&self) -> bool {
+        self.repeat == Repeat::Once && self.elapsed >= self.duration
+    }
+
+    pub fn play(&mut self) {
+        if self.is_finished() {
+            self.elapsed = 0.0;
+        }
+        self.playing = true;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMETERS: [(&str, f64); 1] = [("exponent", 2.0)];
+
+    fn parse(text: &str) -> Result<Track, ParseError> {
+        Track::parse(text, &Viewport::default(), &PARAMETERS)
+    }
+
+    fn error(text: &str) -> (usize, String) {
+        let error = parse(text).unwrap_err();
+        (error.line, error.message)
+    }
+
+    #[test]
+    fn parse_reads_every_key() {
+        let track = parse(
+            "# a zoom\n\
+             t=0 re=-0.5 im=0.25 zoom=1 easing=in-out\n\
+             \n\
+             t=8 re=-0.75 zoom=1e12 rotation=90 depth=4000 exponent=3 # deep\n",
+        )
+        .unwrap();
+
+        let [first, last] = &track.keyframes[..] else {
+            panic!("expected two keyframes, found {:?}", track.keyframes);
+        };
+        assert_eq!(first.time, 0.0);
+        assert_eq!(first.center.0.to_f64(), -0.5);
+        assert_eq!(first.center.1.to_f64(), 0.25);
+        assert_eq!(first.scale, 2.0);
+        assert_eq!(first.easing, Easing::EaseInOut);
+        assert_eq!(first.parameters, vec![("exponent".to_string(), 2.0)]);
+
+        assert_eq!(last.time, 8.0);
+        assert_eq!(last.scale, 2e-12);
+        assert_eq!(last.rotation, std::f64::consts::FRAC_PI_2);
+        assert_eq!(last.depth, Some(4000));
+        assert_eq!(last.parameters, vec![("exponent".to_string(), 3.0)]);
+        assert_eq!(track.duration(), 8.0);
+    }
+
+    #[test]
+    fn parse_carries_values_over_but_not_easing() {
+        let track = parse("t=0 re=1 zoom=4 easing=in exponent=5\nt=1 im=2").unwrap();
+        let last = &track.keyframes[1];
+        assert_eq!(last.center.0.to_f64(), 1.0);
+        assert_eq!(last.center.1.to_f64(), 2.0);
+        assert_eq!(last.scale, 0.5);
+        assert_eq!(last.parameters, vec![("exponent".to_string(), 5.0)]);
+        assert_eq!(last.easing, Easing::Linear);
+    }
+
+    #[test]
+    fn parse_keeps_double_double_centers() {
+        let track = parse("t=0 re=-0.743643887037158704752191506114774").unwrap();
+        let re = track.keyframes[0].center.0;
+        assert_eq!(re.hi, -0.7436438870371587);
+        assert_ne!(re.lo, 0.0);
+    }
+
+    #[test]
+    fn parse_reports_the_line_of_each_error() {
+        assert_eq!(
+            error("t=0\nzoom"),
+            (2, "expected key=value, found \"zoom\"".into())
+        );
+        assert_eq!(
+            error("t=0 zoom=0"),
+            (1, "zoom has to be positive, found 0".into())
+        );
+        assert_eq!(error("t=x"), (1, "invalid t \"x\"".into()));
+        assert_eq!(error("t=inf"), (1, "invalid t \"inf\"".into()));
+        assert_eq!(error("t=0 depth=-1"), (1, "invalid depth \"-1\"".into()));
+        assert_eq!(error("t=0 speed=2"), (1, "unknown key \"speed\"".into()));
+        assert_eq!(error("# t=0\nre=1"), (2, "missing t".into()));
+        assert_eq!(error("t=1\n\nt=1"), (3, "t=1 has to come after t=1".into()));
+        assert_eq!(error("# nothing\n"), (1, "no keyframes".into()));
+        assert_eq!(
+            error("t=0 easing=bounce"),
+            (
+                1,
+                "unknown easing \"bounce\", expected one of linear, in, out, in-out".into()
+            )
+        );
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn sample_zooms_at_a_steady_rate() {
+        let track = parse("t=0 zoom=1\nt=2 zoom=100").unwrap();
+        let middle = track.sample(1.0).unwrap();
+        assert!(close(middle.scale, 0.2), "{}", middle.scale);
+        assert_eq!(middle.time, 1.0);
+    }
+
+    #[test]
+    fn sample_moves_the_center_in_step_with_the_zoom() {
+        // Without a zoom the center moves evenly.
+        let track = parse("t=0 re=0\nt=2 re=1").unwrap();
+        let re = track.sample(0.5).unwrap().center.0.to_f64();
+        assert!(close(re, 0.25), "{re}");
+
+        // Zooming in 100x, the scale is 90% of the way down halfway through,
+        // and so is the center.
+        let track = parse("t=0 re=0 zoom=1\nt=2 re=1 zoom=100").unwrap();
+        let re = track.sample(1.0).unwrap().center.0.to_f64();
+        assert!(close(re, (2.0 - 0.2) / (2.0 - 0.02)), "{re}");
+    }
+
+    #[test]
+    fn sample_eases_each_segment_by_its_first_keyframe() {
+        let track = parse("t=0 re=0 easing=in\nt=1 re=1 easing=out\nt=2 re=2").unwrap();
+        let re = |time| track.sample(time).unwrap().center.0.to_f64();
+        assert!(close(re(0.5), 0.25), "{}", re(0.5));
+        assert!(close(re(1.5), 1.75), "{}", re(1.5));
+    }
+
+    #[test]
+    fn sample_interpolates_parameters_and_rounds_depth() {
+        let track = parse("t=0 depth=10 exponent=2\nt=1 depth=13 exponent=3").unwrap();
+        let middle = track.sample(0.5).unwrap();
+        assert_eq!(middle.depth, Some(12));
+        assert_eq!(middle.parameters, vec![("exponent".to_string(), 2.5)]);
+    }
+
+    #[test]
+    fn sample_holds_the_first_and_last_keyframes() {
+        let track = parse("t=1 re=1\nt=2 re=2").unwrap();
+        let before = track.sample(0.0).unwrap();
+        assert_eq!(before.time, 0.0);
+        assert_eq!(before.center.0.to_f64(), 1.0);
+        let after = track.sample(5.0).unwrap();
+        assert_eq!(after.time, 5.0);
+        assert_eq!(after.center.0.to_f64(), 2.0);
+
+        let empty = Track {
+            keyframes: Vec::new(),
+        };
+        assert_eq!(empty.sample(0.0), None);
+    }
+
+    fn advance(timeline: &mut Timeline, seconds: f64) {
+        timeline.advance(Duration::from_secs_f64(seconds));
+    }
+
+    #[test]
+    fn timeline_once_stops_at_the_end() {
+        let mut timeline = Timeline::new(2.0, Repeat::Once);
+        advance(&mut timeline, 1.5);
+        assert_eq!(timeline.time(), 1.5);
+        assert_eq!(timeline.progress(), 0.75);
+        assert!(!timeline.is_finished());
+
+        advance(&mut timeline, 1.0);
+        assert_eq!(timeline.time(), 2.0);
+        assert_eq!(timeline.cycle(), 0);
+        assert!(timeline.is_finished());
+        assert!(!timeline.is_playing());
+
+        // Playing a finished timeline starts it over.
+        timeline.play();
+        assert_eq!(timeline.time(), 0.0);
+        assert!(timeline.is_playing());
+    }
+
+    #[test]
+    fn timeline_loop_wraps_around() {
+        let mut timeline = Timeline::new(2.0, Repeat::Loop);
+        advance(&mut timeline, 5.0);
+        assert_eq!(timeline.time(), 1.0);
+        assert_eq!(timeline.cycle(), 2);
+        assert!(timeline.is_playing());
+        assert!(!timeline.is_finished());
+    }
+
+    #[test]
+    fn timeline_ping_pong_plays_back_and_forth() {
+        let mut timeline = Timeline::new(2.0, Repeat::PingPong);
+        advance(&mut timeline, 2.5);
+        assert_eq!(timeline.time(), 1.5);
+        assert_eq!(timeline.cycle(), 0);
+
+        advance(&mut timeline, 2.0);
+        assert_eq!(timeline.time(), 0.5);
+        assert_eq!(timeline.cycle(), 1);
+    }
+
+    #[test]
+    fn timeline_seeks_and_pauses() {
+        let mut timeline = Timeline::new(4.0, Repeat::Once);
+        timeline.pause();
+        advance(&mut timeline, 1.0);
+        assert_eq!(timeline.time(), 0.0);
+
+        timeline.seek(3.0);
+        assert_eq!(timeline.time(), 3.0);
+        timeline.seek(10.0);
+        assert_eq!(timeline.time(), 4.0);
+        timeline.seek(-1.0);
+        assert_eq!(timeline.time(), 0.0);
+
+        timeline.toggle();
+        advance(&mut timeline, 1.0);
+        assert_eq!(timeline.time(), 1.0);
+        timeline.toggle();
+        advance(&mut timeline, 1.0);
+        assert_eq!(timeline.time(), 1.0);
+    }
+}
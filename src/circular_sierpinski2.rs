@@ -0,0 +1,94 @@
+//! A circular take on the Sierpinski carpet, built from nested circles.
+
+use crate::{
+    fractal,
+    shapes::{self, Shape},
+};
+use gpui::{point, px, Pixels, Point};
+use std::f32::consts::PI;
+
+/// Parameters for [`carpet`].
+pub struct CircularCarpet {
+    pub center: Point<Pixels>,
+    pub radius: Pixels,
+    pub angle: f32,
+}
+
+impl Default for CircularCarpet {
+    fn default() -> Self {
+        CircularCarpet {
+            center: point(px(384.), px(384.)),
+            radius: px(360.),
+            angle: 0.0,
+        }
+    }
+}
+
+impl fractal::Fractal for CircularCarpet {
+    fn name(&self) -> &'static str {
+        "Circular Sierpinski carpet"
+    }
+
+    fn max_depth(&self) -> u32 {
+        5
+    }
+
+    fn default_depth(&self) -> u32 {
+        4
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        carpet(self.center, self.radius, depth, self.angle).into()
+    }
+
+    /// The outer circle in pixels, and how far the rings turn, in degrees.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("center.x", self.center.x.0 as f64),
+            ("center.y", self.center.y.0 as f64),
+            ("radius", self.radius.0 as f64),
+            ("angle", self.angle.to_degrees() as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "center.x" => self.center.x = px(value as f32),
+            "center.y" => self.center.y = px(value as f32),
+            "radius" => self.radius = px(value as f32),
+            "angle" => self.angle = (value as f32).to_radians(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Draws a circle at `center`, then recurses into eight circles of a third the
+/// radius arranged around it (rotated by `angle`) plus one in the middle.
+pub fn carpet(center: Point<Pixels>, radius: Pixels, depth: u32, angle: f32) -> Vec<Shape> {
+    recursive(center, radius, depth, angle)
+}
+
+fn recursive(center: Point<Pixels>, radius: Pixels, depth: u32, angle: f32) -> Vec<Shape> {
+    let mut circles = Vec::new();
+
+    if depth == 0 {
+        return circles;
+    }
+
+    circles.push(shapes::circle(radius, center).into());
+
+    let inner_radius = radius / 3.0;
+    let offset = radius * 2.0 / 3.0;
+
+    for i in 0..8 {
+        let circle_angle = i as f32 * PI / 4.0 + angle;
+        let x = center.x + px(offset.0 * circle_angle.cos());
+        let y = center.y + px(offset.0 * circle_angle.sin());
+        circles.extend(recursive(Point { x, y }, inner_radius, depth - 1, angle));
+    }
+
+    circles.extend(recursive(center, inner_radius, depth - 1, angle));
+
+    circles
+}
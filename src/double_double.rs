@@ -0,0 +1,261 @@
+//! Double-double arithmetic: a number kept as the unevaluated sum of two
+//! f64s, for about 106 bits of precision where deep zooms need to place a
+//! point more finely than one f64 can.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// `hi + lo`, with `lo` no bigger than half an ulp of `hi`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DoubleDouble {
+    pub hi: f64,
+    pub lo: f64,
+}
+
+impl DoubleDouble {
+    pub const fn new(hi: f64, lo: f64) -> Self {
+        DoubleDouble { hi, lo }
+    }
+
+    /// The nearest f64.
+    pub fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+
+    /// Reads a decimal number like `-0.743643887037158704752191506114774` or
+    /// `1.5e-20` to the full precision of a double-double, where parsing it
+    /// as an f64 would keep only the first 17 or so digits.
+    pub fn parse(text: &str) -> Option<DoubleDouble> {
+        // Let the standard parser reject anything malformed.
+        if !text.parse::<f64>().ok()?.is_finite() {
+            return None;
+        }
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (mantissa, mut exponent) = match text.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().ok()?),
+            None => (text, 0),
+        };
+
+        // Digits past what a double-double can hold only shift the exponent.
+        const MAX_DIGITS: usize = 34;
+        let mut value = DoubleDouble::default();
+        let mut digits = 0;
+        let mut fraction = false;
+        for ch in mantissa.chars() {
+            if ch == '.' {
+                fraction = true;
+                continue;
+            }
+            let digit = ch.to_digit(10)? as f64;
+            if digits < MAX_DIGITS {
+                value = value * 10.0 + digit;
+                if value.hi != 0.0 {
+                    digits += 1;
+                }
+                // Saturating, as the exponent can already be at the edge of
+                // an i32 and the loop below only needs its sign and size.
+                exponent = exponent.saturating_sub(fraction as i32);
+            } else if !fraction {
+                exponent = exponent.saturating_add(1);
+            }
+        }
+
+        // Scale in steps whose powers of ten an f64 can hold, until the value
+        // is in place or has under- or overflowed, so a huge exponent like
+        // `0e999999999` can't keep the loop going.
+        const MAX_STEP: i32 = 300;
+        while exponent != 0 && value.hi != 0.0 && value.hi.is_finite() {
+            let step = exponent.clamp(-MAX_STEP, MAX_STEP);
+            let power = DoubleDouble::power_of_ten(step.unsigned_abs());
+            value = if step < 0 {
+                value / power
+            } else {
+                value * power
+            };
+            exponent -= step;
+        }
+        Some(if negative { -value } else { value })
+    }
+
+    /// `10^exponent` by repeated squaring.
+    fn power_of_ten(mut exponent: u32) -> Self {
+        let mut power = DoubleDouble::from(1.0);
+        let mut square = DoubleDouble::from(10.0);
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                power = power * square;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                square = square * square;
+            }
+        }
+        power
+    }
+
+    /// Renormalizes `hi + lo` when `|hi| >= |lo|`.
+    fn quick_two_sum(hi: f64, lo: f64) -> Self {
+        let sum = hi + lo;
+        DoubleDouble::new(sum, lo - (sum - hi))
+    }
+
+    /// `a + b` and the rounding error of that sum, exactly.
+    fn two_sum(a: f64, b: f64) -> (f64, f64) {
+        let sum = a + b;
+        let b_part = sum - a;
+        let error = (a - (sum - b_part)) + (b - b_part);
+        (sum, error)
+    }
+
+    /// `a * b` and the rounding error of that product, exactly.
+    fn two_product(a: f64, b: f64) -> (f64, f64) {
+        let product = a * b;
+        (product, a.mul_add(b, -product))
+    }
+}
+
+impl From<f64> for DoubleDouble {
+    fn from(value: f64) -> Self {
+        DoubleDouble::new(value, 0.0)
+    }
+}
+
+impl Add for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, other: DoubleDouble) -> DoubleDouble {
+        let (sum, error) = DoubleDouble::two_sum(self.hi, other.hi);
+        DoubleDouble::quick_two_sum(sum, error + self.lo + other.lo)
+    }
+}
+
+impl Add<f64> for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn add(self, other: f64) -> DoubleDouble {
+        self + DoubleDouble::from(other)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn neg(self) -> DoubleDouble {
+        DoubleDouble::new(-self.hi, -self.lo)
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn sub(self, other: DoubleDouble) -> DoubleDouble {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, other: DoubleDouble) -> DoubleDouble {
+        let (product, error) = DoubleDouble::two_product(self.hi, other.hi);
+        DoubleDouble::quick_two_sum(product, error + self.hi * other.lo + self.lo * other.hi)
+    }
+}
+
+impl Mul<f64> for DoubleDouble {
+    type Output = DoubleDouble;
+
+    fn mul(self, other: f64) -> DoubleDouble {
+        self * DoubleDouble::from(other)
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = DoubleDouble;
+
+    /// Long division, one f64 quotient digit at a time.
+    fn div(self, other: DoubleDouble) -> DoubleDouble {
+        let first = self.hi / other.hi;
+        let remainder = self - other * first;
+        let second = remainder.hi / other.hi;
+        let remainder = remainder - other * second;
+        let third = remainder.hi / other.hi;
+        DoubleDouble::quick_two_sum(first, second) + third
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: DoubleDouble, b: DoubleDouble, tolerance: f64) -> bool {
+        (a - b).to_f64().abs() <= tolerance
+    }
+
+    #[test]
+    fn parse_keeps_what_an_f64_rounds_off() {
+        let tenth = DoubleDouble::parse("0.1").unwrap();
+        assert_eq!(tenth.hi, 0.1);
+        // 0.1 as an f64 is about 5.55e-18 too big.
+        assert!((tenth.lo + 5.551115123125783e-18).abs() < 1e-33);
+
+        let value = DoubleDouble::parse("1.00000000000000000000000000000001").unwrap();
+        assert_eq!(value.hi, 1.0);
+        assert!((value.lo - 1e-32).abs() < 1e-45);
+    }
+
+    #[test]
+    fn parse_reads_signs_and_exponents() {
+        assert_eq!(DoubleDouble::parse("2.5E3").unwrap().to_f64(), 2500.0);
+        assert_eq!(DoubleDouble::parse("+2.5e-3").unwrap().to_f64(), 0.0025);
+        assert_eq!(DoubleDouble::parse("-1.5e-20").unwrap().to_f64(), -1.5e-20);
+        assert_eq!(DoubleDouble::parse("12").unwrap().to_f64(), 12.0);
+    }
+
+    #[test]
+    fn parse_handles_huge_exponents_quickly() {
+        assert_eq!(DoubleDouble::parse("0e999999999").unwrap().to_f64(), 0.0);
+        assert_eq!(DoubleDouble::parse("1e-999999999").unwrap().to_f64(), 0.0);
+        assert_eq!(DoubleDouble::parse("1e-320").unwrap().to_f64(), 1e-320);
+        assert_eq!(DoubleDouble::parse("1e308").unwrap().to_f64(), 1e308);
+        assert_eq!(DoubleDouble::parse("1e999"), None);
+    }
+
+    #[test]
+    fn parse_saturates_exponents_at_the_edge_of_an_i32() {
+        assert_eq!(
+            DoubleDouble::parse("0.5e-2147483648").unwrap().to_f64(),
+            0.0
+        );
+        assert_eq!(
+            DoubleDouble::parse("-0.5e-2147483648").unwrap().to_f64(),
+            0.0
+        );
+        assert_eq!(DoubleDouble::parse("0.0e2147483647").unwrap().to_f64(), 0.0);
+        // Out of an i32's range altogether.
+        assert_eq!(DoubleDouble::parse("1e-2147483649"), None);
+    }
+
+    #[test]
+    fn parse_rejects_what_isnt_a_finite_number() {
+        for text in ["", "abc", "1.2.3", "1e", "--1", "inf", "NaN"] {
+            assert_eq!(DoubleDouble::parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn arithmetic_keeps_the_low_part() {
+        let one = DoubleDouble::from(1.0);
+        assert_eq!((one + 1e-20).lo, 1e-20);
+        assert_eq!((one + 1e-20 - one).to_f64(), 1e-20);
+
+        let tenth = DoubleDouble::parse("0.1").unwrap();
+        assert!(close(tenth * 10.0, one, 1e-31));
+
+        let third = one / DoubleDouble::from(3.0);
+        assert!(close(third * 3.0, one, 1e-31));
+        assert!(close(-third + third, DoubleDouble::default(), 0.0));
+    }
+}
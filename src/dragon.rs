@@ -0,0 +1,93 @@
+//! The Heighway dragon curve.
+
+use crate::{
+    fractal,
+    shapes::{self, Shape},
+};
+use gpui::{point, px, Pixels, Point};
+
+/// Parameters for [`curve`].
+pub struct DragonCurve {
+    pub start: Point<Pixels>,
+    pub end: Point<Pixels>,
+}
+
+impl Default for DragonCurve {
+    fn default() -> Self {
+        DragonCurve {
+            start: point(px(224.), px(384.)),
+            end: point(px(544.), px(384.)),
+        }
+    }
+}
+
+impl fractal::Fractal for DragonCurve {
+    fn name(&self) -> &'static str {
+        "Dragon curve"
+    }
+
+    fn max_depth(&self) -> u32 {
+        18
+    }
+
+    fn default_depth(&self) -> u32 {
+        12
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        vec![Shape::from(curve(self.start, self.end, depth))].into()
+    }
+
+    /// The endpoints of the first segment, in pixels.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("start.x", self.start.x.0 as f64),
+            ("start.y", self.start.y.0 as f64),
+            ("end.x", self.end.x.0 as f64),
+            ("end.y", self.end.y.0 as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        let value = px(value as f32);
+        match name {
+            "start.x" => self.start.x = value,
+            "start.y" => self.start.y = value,
+            "end.x" => self.end.x = value,
+            "end.y" => self.end.y = value,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Folds the segment from `start` to `end` `iterations` times, alternating the
+/// side each new corner is pushed to. Produces one polyline of
+/// `2^iterations` segments.
+pub fn curve(start: Point<Pixels>, end: Point<Pixels>, iterations: u32) -> shapes::Polyline {
+    let mut points = Vec::with_capacity((1 << iterations) + 1);
+    points.push(start);
+    recursive(&mut points, start, end, iterations, true);
+    shapes::polyline(points)
+}
+
+fn recursive(
+    points: &mut Vec<Point<Pixels>>,
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    iterations: u32,
+    is_right: bool,
+) {
+    if iterations == 0 {
+        points.push(end);
+    } else {
+        let mid = Point {
+            x: (start.x + end.x) / 2.0
+                + (end.y - start.y) / 2.0 * if is_right { -1.0 } else { 1.0 },
+            y: (start.y + end.y) / 2.0
+                + (start.x - end.x) / 2.0 * if is_right { -1.0 } else { 1.0 },
+        };
+        recursive(points, start, mid, iterations - 1, true);
+        recursive(points, mid, end, iterations - 1, false);
+    }
+}
@@ -0,0 +1,357 @@
+//! The escape-time loop shared by the Julia and Mandelbrot sets and their
+//! variants, and a tiled renderer so the work can be spread across threads.
+
+use crate::{
+    palette::{Coloring, Histogram, Mapping},
+    raster::Raster,
+    viewport::Viewport,
+};
+use gpui::{point, px, size, Bounds, Pixels};
+use num_complex::Complex;
+use num_traits::Float;
+use std::{
+    f32::consts::LN_2,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+/// A tile size that keeps per-tile overhead low while still filling in
+/// progressively at window sizes.
+pub const TILE_SIZE: usize = 64;
+
+/// How many points per side [`Sampler::new`] looks at to equalize colors.
+const HISTOGRAM_SAMPLES: usize = 128;
+
+/// The range [`Formula::Multibrot`] exponents are kept to. Closer to one the
+/// escape radius and smoothing blow up, and at or below one points never
+/// escape at all.
+pub const MIN_EXPONENT: f32 = 1.5;
+pub const MAX_EXPONENT: f32 = 16.0;
+
+/// The map the escape-time fractals iterate. Each comes in a Julia form,
+/// with `c` fixed and `z` starting at the sampled point, and a Mandelbrot
+/// form, with `z` starting at zero and `c` the sampled point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Formula {
+    /// `z * z + c`.
+    Quadratic,
+    /// `(|Re z| + i |Im z|)^2 + c`. Folding into the first quadrant gives the
+    /// Mandelbrot form its hull and masts.
+    BurningShip,
+    /// `conj(z)^2 + c`, also known as the Mandelbar.
+    Tricorn,
+    /// `z^exponent + c`, for real exponents from [`MIN_EXPONENT`] to
+    /// [`MAX_EXPONENT`]. Build it with [`Formula::multibrot`] to stay in range.
+    Multibrot { exponent: f32 },
+}
+
+impl Formula {
+    /// A [`Formula::Multibrot`] with `exponent` clamped to its range.
+    pub fn multibrot(exponent: f32) -> Self {
+        Formula::Multibrot {
+            exponent: exponent.clamp(MIN_EXPONENT, MAX_EXPONENT),
+        }
+    }
+
+    /// One step of the iteration.
+    pub fn step<T: Float>(&self, z: Complex<T>, c: Complex<T>) -> Complex<T> {
+        match *self {
+            Formula::Quadratic => z * z + c,
+            Formula::BurningShip => {
+                let z = Complex::new(z.re.abs(), z.im.abs());
+                z * z + c
+            }
+            Formula::Tricorn => {
+                let z = z.conj();
+                z * z + c
+            }
+            Formula::Multibrot { exponent } if exponent.fract() == 0.0 => {
+                z.powi(exponent as i32) + c
+            }
+            Formula::Multibrot { exponent } => z.powf(T::from(exponent).unwrap()) + c,
+        }
+    }
+
+    /// The exponent of [`Formula::Multibrot`], as a fractal parameter that
+    /// ranges from [`MIN_EXPONENT`] to [`MAX_EXPONENT`].
+    pub fn parameters(&self) -> Vec<(&'static str, f64)> {
+        match *self {
+            Formula::Multibrot { exponent } => vec![("exponent", exponent as f64)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Sets a parameter listed by [`Formula::parameters`], clamped to its
+    /// range. NaN leaves it as it was.
+    pub fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match (self, name) {
+            (Formula::Multibrot { exponent }, "exponent") => {
+                if !value.is_nan() {
+                    *exponent = (value as f32).clamp(MIN_EXPONENT, MAX_EXPONENT);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The power `z` is raised to, which sets how fast escaping points grow.
+    pub fn degree(&self) -> f32 {
+        match *self {
+            Formula::Multibrot { exponent } => exponent,
+            _ => 2.0,
+        }
+    }
+
+    /// Past this distance from the origin a point is sure to escape: 2 for
+    /// degree 2 and up, further out for lower degrees, which grow slower.
+    pub fn escape_radius(&self) -> f32 {
+        let degree = self.degree().max(MIN_EXPONENT);
+        if degree >= 2.0 {
+            2.0
+        } else {
+            2f32.powf(1.0 / (degree - 1.0))
+        }
+    }
+}
+
+/// Iterates `formula` from `z` until `z` leaves the escape radius and returns
+/// the normalized iteration count, a continuous version of the number of
+/// steps that took. Points that don't escape within `max_iterations` return
+/// `None`. Runs in f32 or f64, see [`Precision`].
+pub fn escape<T: Float>(
+    formula: Formula,
+    mut z: Complex<T>,
+    c: Complex<T>,
+    max_iterations: u32,
+) -> Option<f32> {
+    let radius_sqr = T::from(formula.escape_radius().powi(2)).unwrap();
+    for i in 0..max_iterations {
+        if z.norm_sqr() > radius_sqr {
+            let norm = z.norm().to_f32().unwrap_or(f32::MAX);
+            return Some(smooth_count(i, norm, formula.degree()));
+        }
+        z = formula.step(z, c);
+    }
+    None
+}
+
+/// Blends whole iteration counts by how far past the escape radius `norm`
+/// landed, so neighbouring pixels don't fall into hard bands. `degree` is the
+/// power the iteration raises `z` to, taken as at least [`MIN_EXPONENT`].
+pub fn smooth_count(iterations: u32, norm: f32, degree: f32) -> f32 {
+    let degree = degree.max(MIN_EXPONENT);
+    (iterations as f32 + 1.0 - (norm.ln() / LN_2).ln() / degree.ln()).max(0.0)
+}
+
+/// The arithmetic the escape-time loop runs in, from cheapest to most
+/// precise. f32 pixelates past a zoom of about 1e5 and f64 past about 1e13.
+/// Beyond that, [`crate::perturbation`] iterates one reference orbit in
+/// double-double precision and each pixel as a small difference from it,
+/// which holds to a zoom of about 1e28.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision {
+    Single,
+    Double,
+    Perturbation,
+}
+
+impl Precision {
+    /// The cheapest precision that still tells neighbouring pixels of
+    /// `viewport` apart. Only [`Formula::Quadratic`] can be perturbed; the
+    /// other formulas stop at f64.
+    pub fn for_view(viewport: &Viewport, formula: Formula) -> Precision {
+        // Neighbouring pixels should be plenty of ulps apart wherever the
+        // orbit goes, which is out to the escape radius at least.
+        let magnitude = (viewport.center.norm() + viewport.scale).max(2.0);
+        let relative = viewport.units_per_pixel() / magnitude;
+        if relative > 1e-5 {
+            Precision::Single
+        } else if relative > 1e-13 || formula != Formula::Quadratic {
+            Precision::Double
+        } else {
+            Precision::Perturbation
+        }
+    }
+}
+
+/// A rectangle of canvas pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Tile {
+    /// Where the tile sits on the canvas.
+    pub fn bounds(&self) -> Bounds<Pixels> {
+        Bounds::new(
+            point(px(self.x as f32), px(self.y as f32)),
+            size(px(self.width as f32), px(self.height as f32)),
+        )
+    }
+}
+
+/// Covers a `width` x `height` canvas with tiles of at most `tile_size`
+/// pixels square, row by row. Tiles on the right and bottom edges are cut to fit.
+pub fn tiles(width: usize, height: usize, tile_size: usize) -> Vec<Tile> {
+    let tile_size = tile_size.max(1);
+    let mut tiles = Vec::new();
+    for y in (0..height).step_by(tile_size) {
+        for x in (0..width).step_by(tile_size) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: tile_size.min(width - x),
+                height: tile_size.min(height - y),
+            });
+        }
+    }
+    tiles
+}
+
+/// Colors any tile of a fixed canvas, from any thread.
+pub trait TileRenderer: Send + Sync {
+    /// The canvas size, in pixels.
+    fn size(&self) -> (usize, usize);
+
+    /// A raster the size of `tile`.
+    fn render_tile(&self, tile: Tile) -> Raster;
+
+    fn render(&self) -> Raster {
+        let (width, height) = self.size();
+        self.render_tile(Tile {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        })
+    }
+}
+
+/// Sets up a [`TileRenderer`] from its own copies of a fractal's viewport and
+/// parameters. Setting up can be slow, sampling a histogram to equalize
+/// colors against or iterating a reference orbit, so a view runs it off the
+/// UI thread.
+pub type RendererBuilder = Box<dyn FnOnce() -> Arc<dyn TileRenderer> + Send>;
+
+/// Renders every tile of `renderer` across all available cores and stitches
+/// them together. For callers without an executor of their own, like
+/// headless exports.
+pub fn render_parallel(renderer: &dyn TileRenderer, tile_size: usize) -> Raster {
+    let (width, height) = renderer.size();
+    let tiles = tiles(width, height, tile_size);
+    let next_tile = AtomicUsize::new(0);
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+
+    let rendered: Vec<(Tile, Raster)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut rendered = Vec::new();
+                    while let Some(tile) = tiles.get(next_tile.fetch_add(1, Ordering::Relaxed)) {
+                        rendered.push((*tile, renderer.render_tile(*tile)));
+                    }
+                    rendered
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("tile worker panicked"))
+            .collect()
+    });
+
+    let mut raster = Raster::new(width, height);
+    for (tile, tile_raster) in rendered {
+        raster.blit(tile.x, tile.y, &tile_raster);
+    }
+    raster
+}
+
+/// A [`TileRenderer`] that samples the plane through `viewport` and colors
+/// each pixel by the escape count `iterations` returns for it. `iterations`
+/// is given the pixel's offset from the viewport's center rather than its
+/// point, so deep zooms can keep the precision adding the center would lose.
+pub struct Sampler<F> {
+    pub viewport: Viewport,
+    pub max_iterations: u32,
+    pub coloring: Coloring,
+    pub iterations: F,
+    histogram: Option<Histogram>,
+}
+
+impl<F> Sampler<F>
+where
+    F: Fn(Complex<f64>) -> Option<f32> + Send + Sync,
+{
+    /// For [`Mapping::Equalized`] this samples a coarse grid of the viewport up
+    /// front, so every tile is colored against the same histogram.
+    pub fn new(viewport: Viewport, max_iterations: u32, coloring: Coloring, iterations: F) -> Self {
+        let histogram = (coloring.mapping == Mapping::Equalized).then(|| {
+            let iterations = &iterations;
+            let step_x = viewport.width as f64 / HISTOGRAM_SAMPLES as f64;
+            let step_y = viewport.height as f64 / HISTOGRAM_SAMPLES as f64;
+            let counts = (0..HISTOGRAM_SAMPLES).flat_map(|y| {
+                (0..HISTOGRAM_SAMPLES).filter_map(move |x| {
+                    let x = (x as f64 + 0.5) * step_x;
+                    let y = (y as f64 + 0.5) * step_y;
+                    iterations(viewport.offset(x, y))
+                })
+            });
+            Histogram::new(counts.collect::<Vec<_>>(), max_iterations)
+        });
+
+        Sampler {
+            viewport,
+            max_iterations,
+            coloring,
+            iterations,
+            histogram,
+        }
+    }
+}
+
+impl<F> TileRenderer for Sampler<F>
+where
+    F: Fn(Complex<f64>) -> Option<f32> + Send + Sync,
+{
+    fn size(&self) -> (usize, usize) {
+        (self.viewport.width, self.viewport.height)
+    }
+
+    fn render_tile(&self, tile: Tile) -> Raster {
+        let mut raster = Raster::new(tile.width, tile.height);
+
+        for y in 0..tile.height {
+            for x in 0..tile.width {
+                let offset = self
+                    .viewport
+                    .offset((tile.x + x) as f64, (tile.y + y) as f64);
+                let count = (self.iterations)(offset);
+                let color =
+                    self.coloring
+                        .color(count, self.max_iterations, self.histogram.as_ref());
+                raster.set_color(x, y, color);
+            }
+        }
+
+        raster
+    }
+}
+
+/// Calls `iterations` with every canvas pixel's offset from the center of
+/// `viewport` and colors each pixel by its escape count.
+pub fn render(
+    viewport: &Viewport,
+    max_iterations: u32,
+    coloring: &Coloring,
+    iterations: impl Fn(Complex<f64>) -> Option<f32> + Send + Sync,
+) -> Raster {
+    Sampler::new(*viewport, max_iterations, coloring.clone(), iterations).render()
+}
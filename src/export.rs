@@ -0,0 +1,192 @@
+//! Offscreen rendering to image files, without a window: PNGs for every
+//! fractal, SVGs for fractals made of shapes.
+
+use crate::{
+    animation::Track,
+    escape_time::{self, TILE_SIZE},
+    fractal::{Fractal, FractalOutput},
+    raster::Raster,
+    svg,
+};
+use gpui::{point, px, Pixels, Point};
+use std::{fmt, path::Path};
+
+#[derive(Debug)]
+pub enum ExportError {
+    /// The named fractal has nothing to write in the requested format.
+    Unsupported(&'static str),
+    Image(image::ImageError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportError::Unsupported(name) => {
+                write!(f, "{name} can't be exported in this format")
+            }
+            ExportError::Image(error) => write!(f, "failed to write image: {error}"),
+            ExportError::Io(error) => write!(f, "failed to write file: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExportError::Unsupported(_) => None,
+            ExportError::Image(error) => Some(error),
+            ExportError::Io(error) => Some(error),
+        }
+    }
+}
+
+impl From<image::ImageError> for ExportError {
+    fn from(error: image::ImageError) -> Self {
+        ExportError::Image(error)
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::Io(error)
+    }
+}
+
+/// Renders `fractal` at `depth` onto a `width` x `height` raster. Fractals
+/// with a viewport show the same part of the plane it currently does, with
+/// the tiles of a [`Fractal::tile_renderer`] spread over every core. Anything
+/// else is scaled to fill the raster, the way [`svg`] fits its view box.
+pub fn render(fractal: &mut dyn Fractal, depth: u32, width: usize, height: usize) -> Raster {
+    let Some(viewport) = fractal.viewport_mut() else {
+        let output = fractal.generate(depth);
+        let (mut min, mut max) = svg::bounds(&output.shapes);
+        if let Some(image) = &output.image {
+            min = point(min.x.min(0.0), min.y.min(0.0));
+            max = point(
+                max.x.max(image.width as f32),
+                max.y.max(image.height as f32),
+            );
+        }
+        let size = max - min;
+        let scale = (width as f32 / size.x).min(height as f32 / size.y);
+        let offset = point(
+            (width as f32 - size.x * scale) / 2.0 - min.x * scale,
+            (height as f32 - size.y * scale) / 2.0 - min.y * scale,
+        );
+        return rasterize(&output, width, height, scale, offset);
+    };
+    let original = *viewport;
+    viewport.width = width;
+    viewport.height = height;
+
+    let raster = match fractal.tile_renderer(depth) {
+        Some(build) => escape_time::render_parallel(build().as_ref(), TILE_SIZE),
+        None => rasterize(
+            &fractal.generate(depth),
+            width,
+            height,
+            1.0,
+            point(0.0, 0.0),
+        ),
+    };
+    if let Some(viewport) = fractal.viewport_mut() {
+        *viewport = original;
+    }
+    raster
+}
+
+/// Draws `output` onto a new raster, each point moved to `point * scale +
+/// offset`. The image goes down first, then the shapes in order.
+fn rasterize(
+    output: &FractalOutput,
+    width: usize,
+    height: usize,
+    scale: f32,
+    offset: Point<f32>,
+) -> Raster {
+    let mut raster = Raster::new(width, height);
+    if let Some(image) = &output.image {
+        for y in 0..height {
+            for x in 0..width {
+                let source_x = (x as f32 + 0.5 - offset.x) / scale;
+                let source_y = (y as f32 + 0.5 - offset.y) / scale;
+                if source_x >= 0.0 && source_y >= 0.0 {
+                    let (source_x, source_y) = (source_x as usize, source_y as usize);
+                    if source_x < image.width && source_y < image.height {
+                        raster.set(x, y, image.get(source_x, source_y));
+                    }
+                }
+            }
+        }
+    }
+
+    let place = |vertex: Point<Pixels>| {
+        point(
+            px(vertex.x.0 * scale + offset.x),
+            px(vertex.y.0 * scale + offset.y),
+        )
+    };
+    for shape in &output.shapes {
+        for (triangles, color) in shape.triangles() {
+            let triangles: Vec<_> = triangles
+                .into_iter()
+                .map(|triangle| triangle.map(place))
+                .collect();
+            raster.fill_triangles(&triangles, color);
+        }
+    }
+    raster
+}
+
+/// [`render`]s `fractal` and writes the result to `path` as a PNG.
+pub fn png(
+    fractal: &mut dyn Fractal,
+    depth: u32,
+    width: usize,
+    height: usize,
+    path: impl AsRef<Path>,
+) -> Result<(), ExportError> {
+    render(fractal, depth, width, height).save_png(path)?;
+    Ok(())
+}
+
+/// Renders `track` over `fractal` at `fps` frames per second and writes each
+/// frame to `directory` as `frame_00000.png`, `frame_00001.png` and so on,
+/// creating the directory if needed. Frames are drawn at the depth the track
+/// sets, or the fractal's default. `progress` is called with the number of
+/// frames written so far and the total. Returns the number of frames.
+pub fn frames(
+    fractal: &mut dyn Fractal,
+    track: &Track,
+    fps: f64,
+    width: usize,
+    height: usize,
+    directory: impl AsRef<Path>,
+    mut progress: impl FnMut(usize, usize),
+) -> Result<usize, ExportError> {
+    let directory = directory.as_ref();
+    std::fs::create_dir_all(directory)?;
+
+    let count = track.frame_count(fps);
+    for frame in 0..count {
+        let depth = track
+            .apply(frame as f64 / fps, fractal)
+            .unwrap_or_else(|| fractal.default_depth());
+        let path = directory.join(format!("frame_{frame:05}.png"));
+        png(fractal, depth, width, height, path)?;
+        progress(frame + 1, count);
+    }
+    Ok(count)
+}
+
+/// Writes the shapes `fractal` draws at `depth` to `path` as an SVG. Only
+/// fractals built from [`crate::shapes::Shape`]s are supported.
+pub fn svg(fractal: &dyn Fractal, depth: u32, path: impl AsRef<Path>) -> Result<(), ExportError> {
+    let output = fractal.generate(depth);
+    if output.shapes.is_empty() {
+        return Err(ExportError::Unsupported(fractal.name()));
+    }
+    svg::save(&output.shapes, path)?;
+    Ok(())
+}
@@ -0,0 +1,651 @@
+//! A small expression language for formulas typed in at runtime: numbers,
+//! variables, `+ - * / % ^`, parentheses, the constants `pi`, `tau` and `e`,
+//! and the usual math functions.
+
+use std::fmt;
+
+/// A parsed expression, ready to evaluate as often as needed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expression {
+    root: Node,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Node>),
+    Binary(Operator, Box<Node>, Box<Node>),
+    Call(Function, Vec<Node>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    /// The remainder with the sign of the dividend, like Rust's `%`.
+    Remainder,
+    Power,
+}
+
+impl Operator {
+    fn apply(self, a: f64, b: f64) -> f64 {
+        match self {
+            Operator::Add => a + b,
+            Operator::Subtract => a - b,
+            Operator::Multiply => a * b,
+            Operator::Divide => a / b,
+            Operator::Remainder => a % b,
+            Operator::Power => a.powf(b),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Function {
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Sinh,
+    Cosh,
+    Tanh,
+    Sqrt,
+    Abs,
+    Exp,
+    Ln,
+    Log10,
+    Floor,
+    Ceil,
+    Round,
+    Fract,
+    Sign,
+    Min,
+    Max,
+    Pow,
+    Clamp,
+    Mix,
+}
+
+impl Function {
+    const ALL: [(&'static str, Function); 25] = [
+        ("sin", Function::Sin),
+        ("cos", Function::Cos),
+        ("tan", Function::Tan),
+        ("asin", Function::Asin),
+        ("acos", Function::Acos),
+        ("atan", Function::Atan),
+        ("atan2", Function::Atan2),
+        ("sinh", Function::Sinh),
+        ("cosh", Function::Cosh),
+        ("tanh", Function::Tanh),
+        ("sqrt", Function::Sqrt),
+        ("abs", Function::Abs),
+        ("exp", Function::Exp),
+        ("ln", Function::Ln),
+        ("log10", Function::Log10),
+        ("floor", Function::Floor),
+        ("ceil", Function::Ceil),
+        ("round", Function::Round),
+        ("fract", Function::Fract),
+        ("sign", Function::Sign),
+        ("min", Function::Min),
+        ("max", Function::Max),
+        ("pow", Function::Pow),
+        ("clamp", Function::Clamp),
+        ("mix", Function::Mix),
+    ];
+
+    fn from_name(name: &str) -> Option<Function> {
+        Function::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, function)| *function)
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Atan2 | Function::Min | Function::Max | Function::Pow => 2,
+            Function::Clamp | Function::Mix => 3,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, args: &[f64]) -> f64 {
+        let x = args[0];
+        match self {
+            Function::Sin => x.sin(),
+            Function::Cos => x.cos(),
+            Function::Tan => x.tan(),
+            Function::Asin => x.asin(),
+            Function::Acos => x.acos(),
+            Function::Atan => x.atan(),
+            Function::Atan2 => x.atan2(args[1]),
+            Function::Sinh => x.sinh(),
+            Function::Cosh => x.cosh(),
+            Function::Tanh => x.tanh(),
+            Function::Sqrt => x.sqrt(),
+            Function::Abs => x.abs(),
+            Function::Exp => x.exp(),
+            Function::Ln => x.ln(),
+            Function::Log10 => x.log10(),
+            Function::Floor => x.floor(),
+            Function::Ceil => x.ceil(),
+            Function::Round => x.round(),
+            Function::Fract => x - x.floor(),
+            Function::Sign => {
+                if x == 0.0 {
+                    0.0
+                } else {
+                    x.signum()
+                }
+            }
+            Function::Min => x.min(args[1]),
+            Function::Max => x.max(args[1]),
+            Function::Pow => x.powf(args[1]),
+            // Not f64::clamp, which panics when the bounds are the wrong way
+            // round.
+            Function::Clamp => x.max(args[1]).min(args[2]),
+            Function::Mix => x + (args[1] - x) * args[2],
+        }
+    }
+}
+
+/// Where and why an expression failed to parse.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Counting characters from zero.
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.position + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl Expression {
+    /// Parses `text` like `384 + radius * cos(t * tau / points)`. Operators
+    /// bind as usual, `^` tightest and to the right, so `-2^2` is `-4`.
+    pub fn parse(text: &str) -> Result<Expression, ParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            next: 0,
+            depth: 0,
+        };
+        let root = parser.sum()?;
+        match parser.peek() {
+            (_, Token::End) => Ok(Expression { root }),
+            (position, token) => Err(ParseError {
+                position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    /// The expression's value with `variables` given by name. Variables
+    /// missing from the list are NaN.
+    pub fn eval(&self, variables: &[(&str, f64)]) -> f64 {
+        self.root.eval(variables)
+    }
+
+    /// Every variable the expression reads, once each, in the order they
+    /// first appear.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        self.root.collect_variables(&mut names);
+        names
+    }
+}
+
+impl Node {
+    fn eval(&self, variables: &[(&str, f64)]) -> f64 {
+        match self {
+            Node::Number(value) => *value,
+            Node::Variable(name) => variables
+                .iter()
+                .find(|(n, _)| n == name)
+                .map_or(f64::NAN, |(_, value)| *value),
+            Node::Negate(node) => -node.eval(variables),
+            Node::Binary(operator, a, b) => operator.apply(a.eval(variables), b.eval(variables)),
+            Node::Call(function, args) => {
+                let args: Vec<f64> = args.iter().map(|arg| arg.eval(variables)).collect();
+                function.apply(&args)
+            }
+        }
+    }
+
+    fn collect_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match self {
+            Node::Number(_) => {}
+            Node::Variable(name) => {
+                if !names.contains(&name.as_str()) {
+                    names.push(name);
+                }
+            }
+            Node::Negate(node) => node.collect_variables(names),
+            Node::Binary(_, a, b) => {
+                a.collect_variables(names);
+                b.collect_variables(names);
+            }
+            Node::Call(_, args) => {
+                for arg in args {
+                    arg.collect_variables(names);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Identifier(String),
+    Symbol(char),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Number(value) => write!(f, "number {value}"),
+            Token::Identifier(name) => write!(f, "{name:?}"),
+            Token::Symbol(symbol) => write!(f, "'{symbol}'"),
+            Token::End => write!(f, "end of formula"),
+        }
+    }
+}
+
+/// Splits `text` into tokens, each with the position it starts at.
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Tokens::new(text).collect::<Result<Vec<_>, _>>()?;
+    tokens.push((text.chars().count(), Token::End));
+    Ok(tokens)
+}
+
+/// Every name in `text` that reads as a variable, once each, in the order
+/// they first appear. Unlike [`Expression::variables`] this works on text
+/// that doesn't parse, skipping whatever can't be tokenized.
+pub fn identifiers(text: &str) -> Vec<String> {
+    let mut tokens = Tokens::new(text).filter_map(Result::ok).peekable();
+    let mut names: Vec<String> = Vec::new();
+    while let Some((_, token)) = tokens.next() {
+        let Token::Identifier(name) = token else {
+            continue;
+        };
+        let call = matches!(tokens.peek(), Some((_, Token::Symbol('('))));
+        let constant = CONSTANTS.iter().any(|(n, _)| *n == name);
+        if !call && !constant && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Names that are numbers rather than variables.
+const CONSTANTS: [(&str, f64); 3] = [
+    ("pi", std::f64::consts::PI),
+    ("tau", std::f64::consts::TAU),
+    ("e", std::f64::consts::E),
+];
+
+/// The tokens of `text` one at a time. Something that isn't a token is an
+/// error, and tokenizing carries on after it.
+struct Tokens {
+    chars: Vec<char>,
+    next: usize,
+}
+
+impl Tokens {
+    fn new(text: &str) -> Self {
+        Tokens {
+            chars: text.chars().collect(),
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for Tokens {
+    type Item = Result<(usize, Token), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chars = &self.chars;
+        let mut i = self.next;
+        while i < chars.len() && chars[i].is_whitespace() {
+            i += 1;
+        }
+        let start = i;
+        let ch = *chars.get(i)?;
+        let token = if ch.is_ascii_digit() || ch == '.' {
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Only read an exponent if digits follow, leaving the `e` of
+            // something like `2e` to be reported by the parser.
+            if i < chars.len() && matches!(chars[i], 'e' | 'E') {
+                let digits = match chars.get(i + 1) {
+                    Some('+' | '-') => i + 2,
+                    _ => i + 1,
+                };
+                if chars.get(digits).is_some_and(|ch| ch.is_ascii_digit()) {
+                    i = digits;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let number: String = chars[start..i].iter().collect();
+            number
+                .parse()
+                .map(Token::Number)
+                .map_err(|_| format!("invalid number {number:?}"))
+        } else if ch.is_alphabetic() || ch == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            Ok(Token::Identifier(chars[start..i].iter().collect()))
+        } else {
+            i += 1;
+            if "+-*/%^(),".contains(ch) {
+                Ok(Token::Symbol(ch))
+            } else {
+                Err(format!("unexpected '{ch}'"))
+            }
+        };
+        self.next = i;
+        Some(
+            token
+                .map(|token| (start, token))
+                .map_err(|message| ParseError {
+                    position: start,
+                    message,
+                }),
+        )
+    }
+}
+
+/// How deeply parentheses, signs and powers may nest, so a pathological
+/// formula is reported instead of overflowing the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent over the tokens, one method per precedence level.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+    /// How many [`Parser::unary`] calls are under way. Every nested
+    /// subexpression goes through one.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> (usize, Token) {
+        self.tokens[self.next].clone()
+    }
+
+    fn advance(&mut self) -> (usize, Token) {
+        let token = self.peek();
+        if token.1 != Token::End {
+            self.next += 1;
+        }
+        token
+    }
+
+    /// Consumes `symbol` if it comes next.
+    fn eat(&mut self, symbol: char) -> bool {
+        let found = self.peek().1 == Token::Symbol(symbol);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ParseError> {
+        if self.eat(symbol) {
+            return Ok(());
+        }
+        let (position, token) = self.peek();
+        Err(ParseError {
+            position,
+            message: format!("expected '{symbol}', found {token}"),
+        })
+    }
+
+    fn sum(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.product()?;
+        loop {
+            let operator = if self.eat('+') {
+                Operator::Add
+            } else if self.eat('-') {
+                Operator::Subtract
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Node, ParseError> {
+        let mut node = self.unary()?;
+        loop {
+            let operator = if self.eat('*') {
+                Operator::Multiply
+            } else if self.eat('/') {
+                Operator::Divide
+            } else if self.eat('%') {
+                Operator::Remainder
+            } else {
+                return Ok(node);
+            };
+            node = Node::Binary(operator, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Node, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError {
+                position: self.peek().0,
+                message: format!("formula nests more than {MAX_DEPTH} deep"),
+            });
+        }
+        self.depth += 1;
+        let node = self.signed();
+        self.depth -= 1;
+        node
+    }
+
+    fn signed(&mut self) -> Result<Node, ParseError> {
+        if self.eat('-') {
+            return Ok(Node::Negate(Box::new(self.unary()?)));
+        }
+        if self.eat('+') {
+            return self.unary();
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Node, ParseError> {
+        let base = self.atom()?;
+        if self.eat('^') {
+            let exponent = self.unary()?;
+            return Ok(Node::Binary(
+                Operator::Power,
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn atom(&mut self) -> Result<Node, ParseError> {
+        let (position, token) = self.advance();
+        match token {
+            Token::Number(value) => Ok(Node::Number(value)),
+            Token::Symbol('(') => {
+                let node = self.sum()?;
+                self.expect(')')?;
+                Ok(node)
+            }
+            Token::Identifier(name) if self.peek().1 == Token::Symbol('(') => {
+                self.call(position, &name)
+            }
+            Token::Identifier(name) => Ok(CONSTANTS
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map_or(Node::Variable(name), |(_, value)| Node::Number(*value))),
+            token => Err(ParseError {
+                position,
+                message: format!("unexpected {token}"),
+            }),
+        }
+    }
+
+    /// A call to the function `name`, from its opening parenthesis on.
+    fn call(&mut self, position: usize, name: &str) -> Result<Node, ParseError> {
+        let function = Function::from_name(name).ok_or_else(|| ParseError {
+            position,
+            message: format!("unknown function {name:?}"),
+        })?;
+        self.expect('(')?;
+        let mut args = Vec::new();
+        if !self.eat(')') {
+            loop {
+                args.push(self.sum()?);
+                if self.eat(')') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        if args.len() != function.arity() {
+            return Err(ParseError {
+                position,
+                message: format!(
+                    "{name} takes {} argument{}, found {}",
+                    function.arity(),
+                    if function.arity() == 1 { "" } else { "s" },
+                    args.len()
+                ),
+            });
+        }
+        Ok(Node::Call(function, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(text: &str) -> f64 {
+        Expression::parse(text).unwrap().eval(&[])
+    }
+
+    fn error(text: &str) -> (usize, String) {
+        let error = Expression::parse(text).unwrap_err();
+        (error.position, error.message)
+    }
+
+    #[test]
+    fn operators_bind_by_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7.0);
+        assert_eq!(eval("(1 + 2) * 3"), 9.0);
+        assert_eq!(eval("10 - 4 - 3"), 3.0);
+        assert_eq!(eval("12 / 3 / 2"), 2.0);
+        assert_eq!(eval("7 % 4 * 2"), 6.0);
+        assert_eq!(eval("-7 % 4"), -3.0);
+        assert_eq!(eval("2 * 3 ^ 2"), 18.0);
+    }
+
+    #[test]
+    fn power_is_right_associative_and_binds_tighter_than_negation() {
+        assert_eq!(eval("2 ^ 3 ^ 2"), 512.0);
+        assert_eq!(eval("-2 ^ 2"), -4.0);
+        assert_eq!(eval("(-2) ^ 2"), 4.0);
+        assert_eq!(eval("2 ^ -1"), 0.5);
+        assert_eq!(eval("--+2"), 2.0);
+    }
+
+    #[test]
+    fn numbers_read_decimals_and_exponents() {
+        assert_eq!(eval("1.5"), 1.5);
+        assert_eq!(eval(".25"), 0.25);
+        assert_eq!(eval("2e3"), 2000.0);
+        assert_eq!(eval("2E+3"), 2000.0);
+        assert_eq!(eval("25e-1"), 2.5);
+        // Without digits after it the `e` is the constant, multiplied by
+        // nothing, so it doesn't parse.
+        assert_eq!(error("2e"), (1, "unexpected \"e\"".into()));
+        assert_eq!(error("1.2.3"), (0, "invalid number \"1.2.3\"".into()));
+        assert_eq!(error("2 $ 3"), (2, "unexpected '$'".into()));
+    }
+
+    #[test]
+    fn constants_and_functions() {
+        assert_eq!(eval("pi"), std::f64::consts::PI);
+        assert_eq!(eval("tau / 2"), std::f64::consts::PI);
+        assert_eq!(eval("ln(e)"), 1.0);
+        assert_eq!(eval("max(1, min(5, 3))"), 3.0);
+        assert_eq!(eval("clamp(7, 0, 5)"), 5.0);
+        assert_eq!(eval("mix(2, 4, 0.25)"), 2.5);
+        assert_eq!(eval("sign(0)"), 0.0);
+    }
+
+    #[test]
+    fn eval_reads_variables_by_name() {
+        let expression = Expression::parse("a * t + b - a").unwrap();
+        assert_eq!(expression.variables(), vec!["a", "t", "b"]);
+        assert_eq!(expression.eval(&[("a", 2.0), ("b", 1.0), ("t", 3.0)]), 5.0);
+        assert!(expression.eval(&[("a", 2.0)]).is_nan());
+    }
+
+    #[test]
+    fn parse_reports_unknown_functions_and_wrong_arity() {
+        assert_eq!(error("foo(1)"), (0, "unknown function \"foo\"".into()));
+        assert_eq!(
+            error("1 + sin(1, 2)"),
+            (4, "sin takes 1 argument, found 2".into())
+        );
+        assert_eq!(
+            error("atan2(1)"),
+            (0, "atan2 takes 2 arguments, found 1".into())
+        );
+        assert_eq!(error("pow()"), (0, "pow takes 2 arguments, found 0".into()));
+    }
+
+    #[test]
+    fn parse_reports_unbalanced_and_trailing_tokens() {
+        assert_eq!(
+            error("(1 + 2"),
+            (6, "expected ')', found end of formula".into())
+        );
+        assert_eq!(error("1 + 2)"), (5, "unexpected ')'".into()));
+        assert_eq!(error("1 +"), (3, "unexpected end of formula".into()));
+        assert_eq!(error(""), (0, "unexpected end of formula".into()));
+    }
+
+    #[test]
+    fn parse_limits_nesting() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(eval(&nested(MAX_DEPTH - 1)), 1.0);
+        assert!(error(&nested(MAX_DEPTH)).1.contains("nests more than"));
+        assert!(Expression::parse(&"-".repeat(100_000)).is_err());
+        assert!(Expression::parse(&"2^".repeat(100_000)).is_err());
+    }
+
+    #[test]
+    fn identifiers_skip_functions_constants_and_bad_tokens() {
+        assert_eq!(
+            identifiers("radius * cos(t * tau) + e"),
+            vec!["radius", "t"]
+        );
+        assert_eq!(identifiers("a $ b + (a"), vec!["a", "b"]);
+        assert_eq!(identifiers("2x + x2"), vec!["x", "x2"]);
+    }
+}
@@ -0,0 +1,444 @@
+//! Fractal flames, after Scott Draves: a chaos game whose transforms bend
+//! the plane through nonlinear variations, colored by which transforms a
+//! point passed through and tone mapped by log density.
+
+use crate::{
+    escape_time::{RendererBuilder, Tile, TileRenderer},
+    fractal,
+    ifs::Affine,
+    palette::Palette,
+    raster::{self, Raster},
+    rng::Rng,
+    viewport::Viewport,
+};
+use gpui::Rgba;
+use num_complex::Complex;
+use std::{f64::consts::PI, sync::Arc};
+
+/// Each unit of depth plays this many rounds of the chaos game.
+pub const POINTS_PER_DEPTH: usize = 100_000;
+
+/// The first few points are still on their way to the attractor.
+const WARM_UP: usize = 20;
+
+/// The histogram size, 256 MiB worth of buckets, past which a render stops
+/// supersampling as much as asked. It is not a hard cap: at one sample per
+/// pixel the histogram grows with the canvas, 16 bytes a pixel.
+const MAX_HISTOGRAM_CELLS: usize = 4096 * 4096;
+
+/// A nonlinear function applied after a transform's affine map. `r` is the
+/// distance from the origin and `theta` is `atan2(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Variation {
+    Linear,
+    Sinusoidal,
+    Spherical,
+    Swirl,
+    Horseshoe,
+    Polar,
+    Handkerchief,
+    Heart,
+    Disc,
+    Spiral,
+    Hyperbolic,
+    Diamond,
+    /// Takes a random one of the two square roots.
+    Julia,
+}
+
+impl Variation {
+    pub fn apply(&self, x: f64, y: f64, rng: &mut Rng) -> (f64, f64) {
+        let r2 = (x * x + y * y).max(f64::EPSILON);
+        let r = r2.sqrt();
+        let theta = x.atan2(y);
+        match self {
+            Variation::Linear => (x, y),
+            Variation::Sinusoidal => (x.sin(), y.sin()),
+            Variation::Spherical => (x / r2, y / r2),
+            Variation::Swirl => {
+                let (sin, cos) = r2.sin_cos();
+                (x * sin - y * cos, x * cos + y * sin)
+            }
+            Variation::Horseshoe => ((x - y) * (x + y) / r, 2.0 * x * y / r),
+            Variation::Polar => (theta / PI, r - 1.0),
+            Variation::Handkerchief => (r * (theta + r).sin(), r * (theta - r).cos()),
+            Variation::Heart => (r * (theta * r).sin(), -r * (theta * r).cos()),
+            Variation::Disc => {
+                let (sin, cos) = (PI * r).sin_cos();
+                (theta / PI * sin, theta / PI * cos)
+            }
+            Variation::Spiral => ((theta.cos() + r.sin()) / r, (theta.sin() - r.cos()) / r),
+            Variation::Hyperbolic => (theta.sin() / r, r * theta.cos()),
+            Variation::Diamond => (theta.sin() * r.cos(), theta.cos() * r.sin()),
+            Variation::Julia => {
+                let omega = if rng.next_u64() & 1 == 0 { 0.0 } else { PI };
+                let (sin, cos) = (theta / 2.0 + omega).sin_cos();
+                (r.sqrt() * cos, r.sqrt() * sin)
+            }
+        }
+    }
+}
+
+/// An affine map followed by a weighted blend of variations.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    pub affine: Affine,
+    /// How often the chaos game picks this transform.
+    pub weight: f32,
+    /// Where on the palette this transform pulls a point's color, from 0 to 1.
+    pub color: f32,
+    pub variations: Vec<(Variation, f64)>,
+}
+
+impl Transform {
+    pub fn new(affine: Affine, weight: f32, color: f32) -> Self {
+        Transform {
+            affine,
+            weight,
+            color,
+            variations: Vec::new(),
+        }
+    }
+
+    pub fn variation(mut self, variation: Variation, amount: f64) -> Self {
+        self.variations.push((variation, amount));
+        self
+    }
+
+    pub fn apply(&self, x: f64, y: f64, rng: &mut Rng) -> (f64, f64) {
+        let (x, y) = self.affine.apply(x, y);
+        if self.variations.is_empty() {
+            return (x, y);
+        }
+        self.variations
+            .iter()
+            .fold((0.0, 0.0), |(sum_x, sum_y), (variation, amount)| {
+                let (vx, vy) = variation.apply(x, y, rng);
+                (sum_x + amount * vx, sum_y + amount * vy)
+            })
+    }
+}
+
+/// A flame and how to develop it.
+#[derive(Clone, Debug)]
+pub struct Flame {
+    pub name: &'static str,
+    pub transforms: Vec<Transform>,
+    /// Applied to every point before it is plotted, without feeding back into
+    /// the game.
+    pub final_transform: Option<Transform>,
+    pub viewport: Viewport,
+    /// Flames are always tone mapped by density, so only a palette is needed.
+    pub palette: Palette,
+    /// Samples per canvas pixel along each axis, averaged down when tone
+    /// mapping. Lowered, as far as one, for canvases too large to supersample
+    /// within [`MAX_HISTOGRAM_CELLS`].
+    pub supersample: usize,
+    pub gamma: f32,
+    /// Blends between brightening the density (1) and brightening each
+    /// channel on its own (0). Higher values keep colors saturated.
+    pub vibrancy: f32,
+    pub brightness: f32,
+    pub seed: u64,
+}
+
+impl Flame {
+    pub fn new(name: &'static str, transforms: Vec<Transform>, palette: Palette) -> Self {
+        Flame {
+            name,
+            transforms,
+            final_transform: None,
+            viewport: Viewport {
+                scale: 1.2,
+                ..Viewport::default()
+            },
+            palette,
+            supersample: 2,
+            gamma: 2.2,
+            vibrancy: 1.0,
+            brightness: 1.0,
+            seed: 0,
+        }
+    }
+
+    pub fn final_transform(mut self, transform: Transform) -> Self {
+        self.final_transform = Some(transform);
+        self
+    }
+
+    /// Centers the view on `center`, showing `extent` from there to the
+    /// nearest canvas edge.
+    pub fn view(mut self, center: (f64, f64), extent: f64) -> Self {
+        self.viewport.center = Complex::new(center.0, center.1);
+        self.viewport.center_low = Complex::new(0.0, 0.0);
+        self.viewport.scale = extent;
+        self
+    }
+
+    /// A sinusoidal and swirled pair of spirals.
+    pub fn swirl() -> Flame {
+        Flame::new(
+            "Swirl flame",
+            vec![
+                Transform::new(Affine::new(0.56, -0.52, 0.52, 0.56, 0.1, 0.0), 0.6, 0.0)
+                    .variation(Variation::Swirl, 0.6)
+                    .variation(Variation::Linear, 0.4),
+                Transform::new(Affine::new(-0.35, 0.3, -0.3, -0.35, 0.5, 0.25), 0.25, 0.6)
+                    .variation(Variation::Sinusoidal, 1.0),
+                Transform::new(Affine::new(0.4, 0.0, 0.0, 0.4, -0.6, -0.4), 0.15, 1.0)
+                    .variation(Variation::Spherical, 0.7)
+                    .variation(Variation::Linear, 0.3),
+            ],
+            Palette::electric(),
+        )
+        .view((-0.15, -0.1), 1.1)
+    }
+
+    /// Spherical inversions and a Julia square root, wrapped into a ring by
+    /// a final swirl.
+    pub fn spherical() -> Flame {
+        Flame::new(
+            "Spherical flame",
+            vec![
+                Transform::new(Affine::new(-0.68, 0.34, -0.34, -0.68, 0.9, 0.1), 1.0, 0.0)
+                    .variation(Variation::Spherical, 1.0),
+                Transform::new(Affine::new(0.52, -0.48, 0.48, 0.52, -0.4, 0.3), 1.0, 0.6)
+                    .variation(Variation::Spherical, 0.8)
+                    .variation(Variation::Linear, 0.2),
+                Transform::new(Affine::new(0.3, 0.0, 0.0, 0.3, 0.0, -0.8), 0.4, 1.0)
+                    .variation(Variation::Julia, 1.0),
+            ],
+            Palette::fire(),
+        )
+        .final_transform(
+            Transform::new(Affine::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0), 1.0, 0.5)
+                .variation(Variation::Swirl, 0.15)
+                .variation(Variation::Linear, 0.85),
+        )
+        .view((0.0, 0.0), 1.8)
+    }
+
+    /// Heart and disc variations turned about the origin into five petals.
+    pub fn bloom() -> Flame {
+        let (sin, cos) = (2.0 * PI / 5.0).sin_cos();
+        Flame::new(
+            "Bloom flame",
+            vec![
+                Transform::new(Affine::new(cos, -sin, sin, cos, 0.0, 0.0), 1.0, 0.0),
+                Transform::new(Affine::new(0.7, 0.2, -0.2, 0.7, 0.3, 0.1), 1.0, 0.5)
+                    .variation(Variation::Heart, 0.4)
+                    .variation(Variation::Linear, 0.6),
+                Transform::new(Affine::new(-0.4, 0.3, 0.3, 0.4, 0.6, -0.2), 0.5, 1.0)
+                    .variation(Variation::Disc, 1.0),
+            ],
+            Palette::rainbow(),
+        )
+        .view((0.1, 0.07), 0.8)
+    }
+
+    /// Plays `points` rounds of the chaos game, develops the image and returns
+    /// a renderer that hands out tiles of it.
+    pub fn renderer(&self, points: usize) -> FlameRenderer {
+        FlameRenderer {
+            image: self.develop(self.histogram(points)),
+        }
+    }
+
+    /// The flame's supersampling, lowered until the histogram fits in
+    /// [`MAX_HISTOGRAM_CELLS`].
+    fn histogram_supersample(&self) -> usize {
+        let pixels = self.viewport.width * self.viewport.height;
+        let mut supersample = self.supersample.max(1);
+        while supersample > 1 && pixels * supersample * supersample > MAX_HISTOGRAM_CELLS {
+            supersample -= 1;
+        }
+        supersample
+    }
+
+    /// Accumulated palette color and hit count for every supersample.
+    fn histogram(&self, points: usize) -> Vec<[f32; 4]> {
+        let supersample = self.histogram_supersample();
+        let viewport = Viewport {
+            width: self.viewport.width * supersample,
+            height: self.viewport.height * supersample,
+            ..self.viewport
+        };
+        let mut histogram = vec![[0.0f32; 4]; viewport.width * viewport.height];
+        let mut rng = Rng::new(self.seed);
+        let (mut x, mut y) = (rng.next_f64() * 2.0 - 1.0, rng.next_f64() * 2.0 - 1.0);
+        let mut color = rng.next_f32();
+
+        for round in 0..points + WARM_UP {
+            let weights = self.transforms.iter().map(|transform| transform.weight);
+            let Some(ix) = rng.weighted(weights) else {
+                break;
+            };
+            let transform = &self.transforms[ix];
+            (x, y) = transform.apply(x, y, &mut rng);
+            color = (color + transform.color) / 2.0;
+            if !x.is_finite() || !y.is_finite() {
+                // A point flung to infinity restarts somewhere new.
+                (x, y) = (rng.next_f64() * 2.0 - 1.0, rng.next_f64() * 2.0 - 1.0);
+                continue;
+            }
+            if round < WARM_UP {
+                continue;
+            }
+
+            let (mut plot_x, mut plot_y, mut plot_color) = (x, y, color);
+            if let Some(final_transform) = &self.final_transform {
+                (plot_x, plot_y) = final_transform.apply(x, y, &mut rng);
+                plot_color = (color + final_transform.color) / 2.0;
+            }
+            let (px, py) = viewport.to_canvas(Complex::new(plot_x, plot_y));
+            if px >= 0.0
+                && py >= 0.0
+                && (px as usize) < viewport.width
+                && (py as usize) < viewport.height
+            {
+                let Rgba { r, g, b, .. } = self.palette.sample(plot_color);
+                let bucket = &mut histogram[py as usize * viewport.width + px as usize];
+                bucket[0] += r;
+                bucket[1] += g;
+                bucket[2] += b;
+                bucket[3] += 1.0;
+            }
+        }
+        histogram
+    }
+
+    /// Averages each pixel's supersamples and tone maps them: brightness
+    /// follows the log of the hit count, then gamma and vibrancy are applied.
+    fn develop(&self, histogram: Vec<[f32; 4]>) -> Raster {
+        let supersample = self.histogram_supersample();
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let samples = (supersample * supersample) as f32;
+
+        // Without supersampling the histogram already has one bucket per pixel.
+        let pixels = if supersample == 1 {
+            histogram
+        } else {
+            let mut pixels = vec![[0.0f32; 4]; width * height];
+            for (ix, bucket) in histogram.iter().enumerate() {
+                let (x, y) = (ix % (width * supersample), ix / (width * supersample));
+                let pixel = &mut pixels[(y / supersample) * width + x / supersample];
+                for channel in 0..4 {
+                    pixel[channel] += bucket[channel] / samples;
+                }
+            }
+            pixels
+        };
+
+        let max = pixels.iter().map(|pixel| pixel[3]).fold(0.0, f32::max);
+        let log_max = (1.0 + max).ln().max(f32::EPSILON);
+        let inverse_gamma = 1.0 / self.gamma.max(f32::EPSILON);
+        let mut raster = Raster::new(width, height);
+        for (ix, [r, g, b, hits]) in pixels.into_iter().enumerate() {
+            if hits <= 0.0 {
+                continue;
+            }
+            let alpha = ((1.0 + hits).ln() / log_max * self.brightness).min(1.0);
+            let alpha_gamma = alpha.powf(inverse_gamma);
+            let channel = |sum: f32| {
+                let average = sum / hits;
+                let vibrant = average * alpha_gamma;
+                let muted = (average * alpha).powf(inverse_gamma);
+                self.vibrancy * vibrant + (1.0 - self.vibrancy) * muted
+            };
+            let color = Rgba {
+                r: channel(r),
+                g: channel(g),
+                b: channel(b),
+                a: alpha_gamma,
+            };
+            raster.set(ix % width, ix / width, raster::to_rgba8(color));
+        }
+        raster
+    }
+}
+
+impl fractal::Fractal for Flame {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Depth counts [`POINTS_PER_DEPTH`] points.
+    fn max_depth(&self) -> u32 {
+        1000
+    }
+
+    fn default_depth(&self) -> u32 {
+        50
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        self.renderer(depth as usize * POINTS_PER_DEPTH)
+            .render()
+            .into()
+    }
+
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        Some(&mut self.viewport)
+    }
+
+    fn palette(&self) -> Option<&Palette> {
+        Some(&self.palette)
+    }
+
+    fn palette_mut(&mut self) -> Option<&mut Palette> {
+        Some(&mut self.palette)
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("gamma", self.gamma as f64),
+            ("vibrancy", self.vibrancy as f64),
+            ("brightness", self.brightness as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "gamma" => self.gamma = value as f32,
+            "vibrancy" => self.vibrancy = value as f32,
+            "brightness" => self.brightness = value as f32,
+            _ => return false,
+        }
+        true
+    }
+
+    fn tile_renderer(&self, depth: u32) -> Option<RendererBuilder> {
+        let points = depth.min(self.max_depth()) as usize * POINTS_PER_DEPTH;
+        let flame = self.clone();
+        Some(Box::new(move || {
+            Arc::new(flame.renderer(points)) as Arc<dyn TileRenderer>
+        }))
+    }
+}
+
+/// Every preset, in the order a picker should list them.
+pub fn presets() -> Vec<Flame> {
+    vec![Flame::swirl(), Flame::spherical(), Flame::bloom()]
+}
+
+/// Hands out tiles of a developed flame.
+pub struct FlameRenderer {
+    image: Raster,
+}
+
+impl TileRenderer for FlameRenderer {
+    fn size(&self) -> (usize, usize) {
+        (self.image.width, self.image.height)
+    }
+
+    fn render_tile(&self, tile: Tile) -> Raster {
+        let image = &self.image;
+        let mut raster = Raster::new(tile.width, tile.height);
+        for y in 0..tile.height {
+            for x in 0..tile.width {
+                raster.set(x, y, image.get(tile.x + x, tile.y + y));
+            }
+        }
+        raster
+    }
+}
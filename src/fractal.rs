@@ -0,0 +1,116 @@
+//! The [`Fractal`] trait that every generator in this crate implements.
+
+use crate::{
+    escape_time::RendererBuilder,
+    palette::{Coloring, Palette},
+    raster::Raster,
+    shapes::Shape,
+    viewport::Viewport,
+};
+use gpui::{Pixels, Point};
+
+/// Everything a generator can produce: shapes to paint as quads or paths
+/// and an image for `Window::paint_image`, drawn with its top-left corner at
+/// the origin. Both keep their geometry and colors, so they can be rasterized
+/// offscreen as well as painted.
+#[derive(Default)]
+pub struct FractalOutput {
+    pub shapes: Vec<Shape>,
+    pub image: Option<Raster>,
+}
+
+impl FractalOutput {
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty() && self.image.is_none()
+    }
+}
+
+impl From<Vec<Shape>> for FractalOutput {
+    fn from(shapes: Vec<Shape>) -> Self {
+        FractalOutput {
+            shapes,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Raster> for FractalOutput {
+    fn from(raster: Raster) -> Self {
+        FractalOutput {
+            image: Some(raster),
+            ..Default::default()
+        }
+    }
+}
+
+/// A fractal generator. The implementing type is its own parameter struct,
+/// so a viewer can hold any of them as a `Box<dyn Fractal>`.
+pub trait Fractal {
+    fn name(&self) -> &'static str;
+
+    /// The deepest recursion (or iteration count) this fractal can be asked for.
+    fn max_depth(&self) -> u32;
+
+    fn default_depth(&self) -> u32;
+
+    /// Produce the fractal at exactly `depth`. Callers should go through
+    /// [`Fractal::generate`], which keeps `depth` within bounds.
+    fn draw(&self, depth: u32) -> FractalOutput;
+
+    fn generate(&self, depth: u32) -> FractalOutput {
+        self.draw(depth.min(self.max_depth()))
+    }
+
+    /// Fractals drawn on the complex plane hand out their viewport so a view
+    /// can pan and zoom them before generating again.
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        None
+    }
+
+    /// Fractals colored through a palette hand out their coloring so a view
+    /// can show it, or switch palettes before generating again.
+    fn coloring(&self) -> Option<&Coloring> {
+        None
+    }
+
+    fn coloring_mut(&mut self) -> Option<&mut Coloring> {
+        None
+    }
+
+    /// The palette alone. Fractals that color through a palette but have no
+    /// use for a [`Coloring`]'s mapping hand out just this, so a view doesn't
+    /// offer mappings that would change nothing.
+    fn palette(&self) -> Option<&Palette> {
+        self.coloring().map(|coloring| &coloring.palette)
+    }
+
+    fn palette_mut(&mut self) -> Option<&mut Palette> {
+        self.coloring_mut().map(|coloring| &mut coloring.palette)
+    }
+
+    /// Named numbers that shape the fractal beyond its viewport and depth,
+    /// like a Julia set's `c`, with their current values. Animations
+    /// interpolate them between keyframes.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        Vec::new()
+    }
+
+    /// Sets one of [`Fractal::parameters`]. Returns `false` for names the
+    /// fractal doesn't have.
+    fn set_parameter(&mut self, _name: &str, _value: f64) -> bool {
+        false
+    }
+
+    /// Fractals that color every pixel independently can hand out a builder
+    /// for a renderer of their canvas at `depth`, so a view can set it up,
+    /// split the work into tiles and render them, all in the background.
+    fn tile_renderer(&self, _depth: u32) -> Option<RendererBuilder> {
+        None
+    }
+
+    /// Called when the canvas is clicked at `position`. Returning a fractal
+    /// asks the view to show that one instead.
+    fn pick(&self, _position: Point<Pixels>) -> Option<Box<dyn Fractal>> {
+        None
+    }
+}
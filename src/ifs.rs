@@ -0,0 +1,288 @@
+//! Iterated function systems, drawn with the chaos game: a point jumps
+//! between randomly chosen affine maps and every landing spot is counted.
+//! The attractor shows up as the places it lands most.
+
+use crate::{
+    escape_time::{RendererBuilder, Tile, TileRenderer},
+    fractal,
+    palette::{Coloring, Histogram, Mapping, Palette},
+    raster::Raster,
+    rng::Rng,
+    viewport::Viewport,
+};
+use num_complex::Complex;
+use std::sync::Arc;
+
+/// Each unit of depth plays this many rounds of the chaos game.
+pub const POINTS_PER_DEPTH: usize = 100_000;
+
+/// Densities are spread over this many levels before coloring, so the usual
+/// [`Mapping`]s apply.
+const LEVELS: u32 = 256;
+
+/// The first few points are still on their way to the attractor.
+const WARM_UP: usize = 20;
+
+/// `(x, y) -> (a x + b y + e, c x + d y + f)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine {
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+}
+
+impl Affine {
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+        Affine { a, b, c, d, e, f }
+    }
+
+    pub fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        (
+            self.a * x + self.b * y + self.e,
+            self.c * x + self.d * y + self.f,
+        )
+    }
+}
+
+/// Affine maps and how often each one is picked. Points use the usual math
+/// orientation, with y growing upwards.
+#[derive(Clone, Debug)]
+pub struct Ifs {
+    pub name: &'static str,
+    pub maps: Vec<(Affine, f32)>,
+    pub viewport: Viewport,
+    pub coloring: Coloring,
+    pub seed: u64,
+}
+
+impl Ifs {
+    /// An IFS centered on `center`, showing `extent` from there to the
+    /// nearest canvas edge.
+    pub fn new(
+        name: &'static str,
+        maps: impl IntoIterator<Item = (Affine, f32)>,
+        center: (f64, f64),
+        extent: f64,
+    ) -> Self {
+        Ifs {
+            name,
+            maps: maps.into_iter().collect(),
+            viewport: Viewport {
+                // The plane's imaginary axis grows downwards.
+                center: Complex::new(center.0, -center.1),
+                scale: extent,
+                ..Viewport::default()
+            },
+            coloring: Coloring::new(Palette::fire(), Mapping::Linear),
+            seed: 0,
+        }
+    }
+
+    pub fn barnsley_fern() -> Ifs {
+        Ifs::new(
+            "Barnsley fern",
+            [
+                (Affine::new(0.0, 0.0, 0.0, 0.16, 0.0, 0.0), 0.01),
+                (Affine::new(0.85, 0.04, -0.04, 0.85, 0.0, 1.6), 0.85),
+                (Affine::new(0.2, -0.26, 0.23, 0.22, 0.0, 1.6), 0.07),
+                (Affine::new(-0.15, 0.28, 0.26, 0.24, 0.0, 0.44), 0.07),
+            ],
+            (0.25, 5.0),
+            5.2,
+        )
+    }
+
+    /// The same triangle as [`crate::sierpinski`], as three half-size copies.
+    pub fn sierpinski_triangle() -> Ifs {
+        let height = 3f64.sqrt() / 2.0;
+        Ifs::new(
+            "Sierpinski IFS",
+            [
+                (Affine::new(0.5, 0.0, 0.0, 0.5, 0.0, 0.0), 1.0),
+                (Affine::new(0.5, 0.0, 0.0, 0.5, 0.5, 0.0), 1.0),
+                (Affine::new(0.5, 0.0, 0.0, 0.5, 0.25, height / 2.0), 1.0),
+            ],
+            (0.5, height / 2.0),
+            0.55,
+        )
+    }
+
+    /// Eight third-size copies of the unit square, leaving out the middle.
+    pub fn sierpinski_carpet() -> Ifs {
+        let maps = (0..3)
+            .flat_map(|row| (0..3).map(move |column| (row, column)))
+            .filter(|&cell| cell != (1, 1))
+            .map(|(row, column)| {
+                let third = 1.0 / 3.0;
+                (
+                    Affine::new(
+                        third,
+                        0.0,
+                        0.0,
+                        third,
+                        column as f64 * third,
+                        row as f64 * third,
+                    ),
+                    1.0,
+                )
+            });
+        Ifs::new("Sierpinski carpet", maps, (0.5, 0.5), 0.55)
+    }
+
+    /// The same curve as [`crate::dragon`]: `z -> (1 + i) z / 2` and
+    /// `z -> 1 - (1 - i) z / 2`.
+    pub fn heighway_dragon() -> Ifs {
+        Ifs::new(
+            "Heighway dragon IFS",
+            [
+                (Affine::new(0.5, -0.5, 0.5, 0.5, 0.0, 0.0), 1.0),
+                (Affine::new(-0.5, -0.5, 0.5, -0.5, 1.0, 0.0), 1.0),
+            ],
+            (5.0 / 12.0, 1.0 / 6.0),
+            0.82,
+        )
+    }
+
+    /// Plays `points` rounds of the chaos game and returns a renderer for the
+    /// attractor it traced.
+    pub fn renderer(&self, points: usize) -> DensityRenderer {
+        DensityRenderer {
+            viewport: self.viewport,
+            coloring: self.coloring.clone(),
+            density: self.density(points),
+        }
+    }
+
+    fn density(&self, points: usize) -> Density {
+        let (width, height) = (self.viewport.width, self.viewport.height);
+        let mut hits = vec![0u32; width * height];
+        let mut rng = Rng::new(self.seed);
+        let (mut x, mut y) = (0.0, 0.0);
+
+        for round in 0..points + WARM_UP {
+            let Some(ix) = rng.weighted(self.maps.iter().map(|(_, weight)| *weight)) else {
+                break;
+            };
+            (x, y) = self.maps[ix].0.apply(x, y);
+            if round < WARM_UP {
+                continue;
+            }
+            let (px, py) = self.viewport.to_canvas(Complex::new(x, -y));
+            if px >= 0.0 && py >= 0.0 && (px as usize) < width && (py as usize) < height {
+                let hit = &mut hits[py as usize * width + px as usize];
+                *hit = hit.saturating_add(1);
+            }
+        }
+
+        let max = hits.iter().copied().max().unwrap_or(0);
+        let mut density = Density {
+            hits,
+            max,
+            histogram: None,
+        };
+        if self.coloring.mapping == Mapping::Equalized {
+            let levels = density.hits.iter().filter_map(|&hits| density.level(hits));
+            density.histogram = Some(Histogram::new(levels.collect::<Vec<_>>(), LEVELS));
+        }
+        density
+    }
+}
+
+impl fractal::Fractal for Ifs {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Depth counts [`POINTS_PER_DEPTH`] points.
+    fn max_depth(&self) -> u32 {
+        500
+    }
+
+    fn default_depth(&self) -> u32 {
+        20
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        self.renderer(depth as usize * POINTS_PER_DEPTH)
+            .render()
+            .into()
+    }
+
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        Some(&mut self.viewport)
+    }
+
+    fn coloring(&self) -> Option<&Coloring> {
+        Some(&self.coloring)
+    }
+
+    fn coloring_mut(&mut self) -> Option<&mut Coloring> {
+        Some(&mut self.coloring)
+    }
+
+    fn tile_renderer(&self, depth: u32) -> Option<RendererBuilder> {
+        let points = depth.min(self.max_depth()) as usize * POINTS_PER_DEPTH;
+        let ifs = self.clone();
+        Some(Box::new(move || {
+            Arc::new(ifs.renderer(points)) as Arc<dyn TileRenderer>
+        }))
+    }
+}
+
+/// Every preset, in the order a picker should list them.
+pub fn presets() -> Vec<Ifs> {
+    vec![
+        Ifs::barnsley_fern(),
+        Ifs::sierpinski_triangle(),
+        Ifs::sierpinski_carpet(),
+        Ifs::heighway_dragon(),
+    ]
+}
+
+/// How many points landed on each canvas pixel.
+struct Density {
+    hits: Vec<u32>,
+    max: u32,
+    histogram: Option<Histogram>,
+}
+
+/// Colors tiles of a finished chaos game by log density.
+pub struct DensityRenderer {
+    viewport: Viewport,
+    coloring: Coloring,
+    density: Density,
+}
+
+impl Density {
+    /// Log-scaled hits, from 0 to [`LEVELS`], or `None` for empty pixels.
+    fn level(&self, hits: u32) -> Option<f32> {
+        (hits > 0).then(|| {
+            let max = (1.0 + self.max as f32).ln();
+            (1.0 + hits as f32).ln() / max * LEVELS as f32
+        })
+    }
+}
+
+impl TileRenderer for DensityRenderer {
+    fn size(&self) -> (usize, usize) {
+        (self.viewport.width, self.viewport.height)
+    }
+
+    fn render_tile(&self, tile: Tile) -> Raster {
+        let density = &self.density;
+        let mut raster = Raster::new(tile.width, tile.height);
+        for y in 0..tile.height {
+            for x in 0..tile.width {
+                let hits = density.hits[(tile.y + y) * self.viewport.width + tile.x + x];
+                let color =
+                    self.coloring
+                        .color(density.level(hits), LEVELS, density.histogram.as_ref());
+                raster.set_color(x, y, color);
+            }
+        }
+        raster
+    }
+}
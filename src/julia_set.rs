@@ -0,0 +1,167 @@
+//! The filled Julia set of `z * z + c`, or of another [`Formula`], rendered
+//! by escape time.
+
+use crate::{
+    escape_time::{self, Formula, Precision, RendererBuilder, Sampler, TileRenderer},
+    fractal,
+    palette::{Coloring, Mapping, Palette},
+    perturbation::{Form, ReferenceOrbit},
+    raster::Raster,
+    viewport::Viewport,
+};
+use num_complex::Complex;
+use std::sync::Arc;
+
+/// Parameters for [`generate`]. The depth passed to [`fractal::Fractal::generate`]
+/// is the iteration limit.
+pub struct JuliaSet {
+    pub viewport: Viewport,
+    pub formula: Formula,
+    pub c: Complex<f64>,
+    pub coloring: Coloring,
+}
+
+impl Default for JuliaSet {
+    fn default() -> Self {
+        JuliaSet {
+            viewport: Viewport::default(),
+            formula: Formula::Quadratic,
+            c: Complex::new(-0.7, 0.27015),
+            coloring: Coloring::new(Palette::electric(), Mapping::Cyclic { period: 32.0 }),
+        }
+    }
+}
+
+impl JuliaSet {
+    pub fn burning_ship() -> Self {
+        JuliaSet {
+            formula: Formula::BurningShip,
+            c: Complex::new(-1.2, -0.3),
+            ..Default::default()
+        }
+    }
+
+    pub fn tricorn() -> Self {
+        JuliaSet {
+            formula: Formula::Tricorn,
+            c: Complex::new(0.2, 0.6),
+            ..Default::default()
+        }
+    }
+
+    pub fn multibrot(exponent: f32) -> Self {
+        JuliaSet {
+            formula: Formula::multibrot(exponent),
+            c: Complex::new(-0.5, 0.55),
+            ..Default::default()
+        }
+    }
+}
+
+impl fractal::Fractal for JuliaSet {
+    fn name(&self) -> &'static str {
+        match self.formula {
+            Formula::Quadratic => "Julia set",
+            Formula::BurningShip => "Burning Ship Julia set",
+            Formula::Tricorn => "Tricorn Julia set",
+            Formula::Multibrot { .. } => "Multibrot Julia set",
+        }
+    }
+
+    /// High enough for deep zooms, which need far more iterations than the
+    /// default to resolve.
+    fn max_depth(&self) -> u32 {
+        100_000
+    }
+
+    fn default_depth(&self) -> u32 {
+        100
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        generate(&self.viewport, self.formula, self.c, depth, &self.coloring).into()
+    }
+
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        Some(&mut self.viewport)
+    }
+
+    fn tile_renderer(&self, depth: u32) -> Option<RendererBuilder> {
+        let max_iterations = depth.min(self.max_depth());
+        let (viewport, formula, c) = (self.viewport, self.formula, self.c);
+        let coloring = self.coloring.clone();
+        Some(Box::new(move || {
+            Arc::new(sampler(&viewport, formula, c, max_iterations, &coloring))
+                as Arc<dyn TileRenderer>
+        }))
+    }
+
+    fn coloring(&self) -> Option<&Coloring> {
+        Some(&self.coloring)
+    }
+
+    fn coloring_mut(&mut self) -> Option<&mut Coloring> {
+        Some(&mut self.coloring)
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        let mut parameters = vec![("c.re", self.c.re), ("c.im", self.c.im)];
+        parameters.extend(self.formula.parameters());
+        parameters
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "c.re" => self.c.re = value,
+            "c.im" => self.c.im = value,
+            _ => return self.formula.set_parameter(name, value),
+        }
+        true
+    }
+}
+
+/// Samples the part of the plane `viewport` shows, one point per canvas pixel,
+/// iterating `formula` from each point, and colors every pixel by how quickly
+/// its point escapes.
+pub fn generate(
+    viewport: &Viewport,
+    formula: Formula,
+    c: Complex<f64>,
+    max_iterations: u32,
+    coloring: &Coloring,
+) -> Raster {
+    sampler(viewport, formula, c, max_iterations, coloring).render()
+}
+
+/// The tile renderer behind [`generate`], in whichever [`Precision`] the
+/// zoom calls for.
+pub fn sampler(
+    viewport: &Viewport,
+    formula: Formula,
+    c: Complex<f64>,
+    max_iterations: u32,
+    coloring: &Coloring,
+) -> Sampler<impl Fn(Complex<f64>) -> Option<f32> + Send + Sync> {
+    let center = viewport.center;
+    let precision = Precision::for_view(viewport, formula);
+    let reference = (precision == Precision::Perturbation)
+        .then(|| ReferenceOrbit::new(Form::Julia { c }, viewport, max_iterations));
+    Sampler::new(
+        *viewport,
+        max_iterations,
+        coloring.clone(),
+        move |offset: Complex<f64>| {
+            if let Some(reference) = &reference {
+                return reference.escape(offset, max_iterations);
+            }
+            let z = center + offset;
+            if precision == Precision::Single {
+                let z = Complex::<f32>::new(z.re as f32, z.im as f32);
+                let c = Complex::<f32>::new(c.re as f32, c.im as f32);
+                escape_time::escape(formula, z, c, max_iterations)
+            } else {
+                escape_time::escape(formula, z, c, max_iterations)
+            }
+        },
+    )
+}
@@ -0,0 +1,120 @@
+//! The Koch snowflake.
+
+use crate::{
+    fractal,
+    shapes::{self, Shape},
+};
+use gpui::{point, px, Pixels, Point};
+use std::f32::consts::PI;
+
+/// Parameters for [`snowflake`].
+pub struct KochSnowflake {
+    pub start: Point<Pixels>,
+    pub side_length: f32,
+}
+
+impl Default for KochSnowflake {
+    fn default() -> Self {
+        KochSnowflake {
+            start: point(px(184.), px(250.)),
+            side_length: 400.0,
+        }
+    }
+}
+
+impl fractal::Fractal for KochSnowflake {
+    fn name(&self) -> &'static str {
+        "Koch snowflake"
+    }
+
+    fn max_depth(&self) -> u32 {
+        8
+    }
+
+    fn default_depth(&self) -> u32 {
+        5
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        vec![Shape::from(snowflake(self.start, self.side_length, depth))].into()
+    }
+
+    /// The top-left corner and side of the starting triangle, in pixels.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("start.x", self.start.x.0 as f64),
+            ("start.y", self.start.y.0 as f64),
+            ("side_length", self.side_length as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "start.x" => self.start.x = px(value as f32),
+            "start.y" => self.start.y = px(value as f32),
+            "side_length" => self.side_length = value as f32,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// An equilateral triangle with its top-left corner at `start`, each side
+/// replaced `iterations` times by the four-segment Koch generator, as one
+/// closed outline.
+pub fn snowflake(start: Point<Pixels>, side_length: f32, iterations: u32) -> shapes::Polygon {
+    let height = side_length * 3f32.sqrt() / 2.0;
+
+    let p1 = start;
+    let p2 = start + point(px(side_length), px(0.0));
+    let p3 = start + point(px(side_length / 2.0), px(height));
+
+    let mut points = vec![p1];
+    side(&mut points, p1, p2, iterations);
+    side(&mut points, p2, p3, iterations);
+    side(&mut points, p3, p1, iterations);
+    // The last side ends back at `p1`, which the polygon closes to anyway.
+    points.pop();
+
+    shapes::polygon(points)
+}
+
+/// Appends the points after `start` along one side.
+fn side(
+    points: &mut Vec<Point<Pixels>>,
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    iterations: u32,
+) {
+    if iterations == 0 {
+        points.push(end);
+    } else {
+        let delta = end - start;
+        let third = Point {
+            x: delta.x / 3.0,
+            y: delta.y / 3.0,
+        };
+
+        let p1 = start;
+        let p2 = start + third;
+        let p3 = {
+            let angle = PI / 3.0;
+            let rotated_third = Point {
+                x: third.x * angle.cos() - third.y * angle.sin(),
+                y: third.x * angle.sin() + third.y * angle.cos(),
+            };
+            p2 + rotated_third
+        };
+        let p4 = start
+            + Point {
+                x: 2.0 * third.x,
+                y: 2.0 * third.y,
+            };
+        let p5 = end;
+
+        side(points, p1, p2, iterations - 1);
+        side(points, p2, p3, iterations - 1);
+        side(points, p3, p4, iterations - 1);
+        side(points, p4, p5, iterations - 1);
+    }
+}
@@ -0,0 +1,35 @@
+//! Fractal generators for [gpui](https://www.gpui.rs).
+//!
+//! Every generator implements [`Fractal`], so a view can hold any of them as a
+//! `Box<dyn Fractal>` and paint the [`FractalOutput`] it produces. Each module
+//! also keeps a plain function (`koch::snowflake`, `dragon::curve`, ...) for
+//! callers that just want the geometry. The [`shapes`] module has the small
+//! drawing primitives the generators are built from.
+
+pub mod animation;
+pub mod circular_sierpinski2;
+pub mod double_double;
+pub mod dragon;
+pub mod escape_time;
+pub mod export;
+pub mod expression;
+pub mod flame;
+pub mod fractal;
+pub mod ifs;
+pub mod julia_set;
+pub mod koch;
+pub mod lsystem;
+pub mod mandelbrot;
+pub mod newton;
+pub mod palette;
+pub mod perturbation;
+pub mod plot;
+pub mod pythagoras;
+pub mod raster;
+pub mod rng;
+pub mod shapes;
+pub mod sierpinski;
+pub mod svg;
+pub mod viewport;
+
+pub use fractal::{Fractal, FractalOutput};
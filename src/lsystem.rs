@@ -0,0 +1,681 @@
+//! Lindenmayer systems: a string of modules rewritten by production rules,
+//! then walked by a turtle that draws as it goes.
+//!
+//! Modules are a symbol with optional numeric parameters, written `F` or
+//! `F(1.5)` or `A(100,8)`. The turtle understands:
+//!
+//! - any symbol in [`LSystem::draw_symbols`] (`F` by default): move forward,
+//!   drawing. The first parameter is the length, 1 by default.
+//! - `f`: move forward without drawing
+//! - `+` / `-`: turn counter-clockwise / clockwise by the first parameter, in
+//!   degrees, or by [`LSystem::angle`]
+//! - `|`: turn around
+//! - `[` / `]`: save / restore the position and heading, one branch deeper
+//!
+//! Every other symbol only takes part in rewriting. A symbol may have several
+//! [`Production`]s: the ones whose condition holds are picked between by
+//! weight, with a seeded [`Rng`] so the same seed always grows the same plant.
+
+use crate::{
+    fractal,
+    palette::Palette,
+    rng::Rng,
+    shapes::{self, LineCap, LineJoin, Shape},
+};
+use gpui::{point, px, rgb, size, Bounds, Pixels, Point};
+use std::{fmt, sync::Arc};
+
+/// Expansion stops early rather than grow past this many modules.
+const MAX_MODULES: usize = 4_000_000;
+
+/// A symbol and its parameters.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Module {
+    pub symbol: char,
+    pub params: Vec<f32>,
+}
+
+impl Module {
+    pub fn new(symbol: char, params: impl IntoIterator<Item = f32>) -> Self {
+        Module {
+            symbol,
+            params: params.into_iter().collect(),
+        }
+    }
+
+    pub fn param(&self, ix: usize) -> Option<f32> {
+        self.params.get(ix).copied()
+    }
+}
+
+impl From<char> for Module {
+    fn from(symbol: char) -> Self {
+        Module::new(symbol, [])
+    }
+}
+
+/// Reads modules like `F(1.5)+[A(100,8)]`. Whitespace is skipped, and
+/// parameters that aren't numbers are dropped.
+pub fn parse(modules: &str) -> Vec<Module> {
+    let mut parsed = Vec::new();
+    let mut chars = modules.chars().filter(|c| !c.is_whitespace()).peekable();
+    while let Some(symbol) = chars.next() {
+        let mut module = Module::from(symbol);
+        if chars.peek() == Some(&'(') {
+            chars.next();
+            let params: String = chars.by_ref().take_while(|c| *c != ')').collect();
+            module.params = params
+                .split(',')
+                .filter_map(|param| param.parse().ok())
+                .collect();
+        }
+        parsed.push(module);
+    }
+    parsed
+}
+
+type Condition = Arc<dyn Fn(&[f32]) -> bool + Send + Sync>;
+type Successor = Arc<dyn Fn(&[f32]) -> Vec<Module> + Send + Sync>;
+
+/// Rewrites one symbol. Built with [`Production::new`] for a fixed
+/// replacement or [`Production::parametric`] to compute it from the module's
+/// parameters.
+#[derive(Clone)]
+pub struct Production {
+    pub symbol: char,
+    /// How likely this production is picked over the others that apply to
+    /// the same module.
+    pub weight: f32,
+    condition: Option<Condition>,
+    successor: Successor,
+}
+
+impl Production {
+    pub fn new(symbol: char, successor: &str) -> Self {
+        let successor = parse(successor);
+        Production::parametric(symbol, move |_| successor.clone())
+    }
+
+    pub fn parametric(
+        symbol: char,
+        successor: impl Fn(&[f32]) -> Vec<Module> + Send + Sync + 'static,
+    ) -> Self {
+        Production {
+            symbol,
+            weight: 1.0,
+            condition: None,
+            successor: Arc::new(successor),
+        }
+    }
+
+    pub fn weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Only applies to modules whose parameters pass `condition`.
+    pub fn when(mut self, condition: impl Fn(&[f32]) -> bool + Send + Sync + 'static) -> Self {
+        self.condition = Some(Arc::new(condition));
+        self
+    }
+
+    fn applies_to(&self, module: &Module) -> bool {
+        self.symbol == module.symbol
+            && self
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition(&module.params))
+    }
+}
+
+impl fmt::Debug for Production {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Production")
+            .field("symbol", &self.symbol)
+            .field("weight", &self.weight)
+            .field("conditional", &self.condition.is_some())
+            .finish()
+    }
+}
+
+/// How branches are stroked, by how deeply they are nested.
+#[derive(Clone, Debug)]
+pub struct BranchStyle {
+    /// The stroke width of the trunk, in pixels.
+    pub width: f32,
+    /// Each level of nesting multiplies the width by this.
+    pub taper: f32,
+    /// Sampled from the trunk at 0 to the deepest branches at 1.
+    pub colors: Palette,
+}
+
+impl Default for BranchStyle {
+    fn default() -> Self {
+        BranchStyle {
+            width: 1.0,
+            taper: 1.0,
+            colors: Palette::new("White", [(0.0, rgb(0xffffff))]),
+        }
+    }
+}
+
+/// One unbroken run of drawn lines, all at the same depth of nesting.
+#[derive(Clone, Debug)]
+pub struct Branch {
+    pub points: Vec<Point<f32>>,
+    pub depth: u32,
+}
+
+/// An axiom, its productions and how to draw the result.
+#[derive(Clone, Debug)]
+pub struct LSystem {
+    pub name: &'static str,
+    pub axiom: Vec<Module>,
+    pub productions: Vec<Production>,
+    /// How far `+` and `-` turn by default, in degrees.
+    pub angle: f32,
+    /// The turtle's starting heading, in degrees counter-clockwise from the
+    /// positive x axis.
+    pub heading: f32,
+    /// The symbols that draw a line forward.
+    pub draw_symbols: String,
+    /// Picks between productions that apply to the same module.
+    pub seed: u64,
+    pub style: BranchStyle,
+    /// The drawing is scaled to fit inside these bounds, keeping its aspect
+    /// ratio, so the step length never needs tuning per depth.
+    pub bounds: Bounds<Pixels>,
+    pub max_depth: u32,
+    pub default_depth: u32,
+}
+
+impl LSystem {
+    pub fn new(name: &'static str, axiom: &str, angle: f32) -> Self {
+        LSystem {
+            name,
+            axiom: parse(axiom),
+            productions: Vec::new(),
+            angle,
+            heading: 0.0,
+            draw_symbols: "F".into(),
+            seed: 0,
+            style: BranchStyle::default(),
+            bounds: Bounds::new(point(px(40.), px(40.)), size(px(688.), px(688.))),
+            max_depth: 8,
+            default_depth: 4,
+        }
+    }
+
+    /// Rewrites every `symbol` to `replacement` on each iteration, replacing
+    /// any productions `symbol` already had.
+    pub fn rule(mut self, symbol: char, replacement: &str) -> Self {
+        self.productions
+            .retain(|production| production.symbol != symbol);
+        self.production(Production::new(symbol, replacement))
+    }
+
+    /// Adds `replacement` as one of several choices for `symbol`, picked in
+    /// proportion to `weight`.
+    pub fn stochastic_rule(self, symbol: char, weight: f32, replacement: &str) -> Self {
+        self.production(Production::new(symbol, replacement).weight(weight))
+    }
+
+    pub fn production(mut self, production: Production) -> Self {
+        self.productions.push(production);
+        self
+    }
+
+    pub fn heading(mut self, heading: f32) -> Self {
+        self.heading = heading;
+        self
+    }
+
+    pub fn draw_symbols(mut self, symbols: impl Into<String>) -> Self {
+        self.draw_symbols = symbols.into();
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn style(mut self, style: BranchStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn depths(mut self, default_depth: u32, max_depth: u32) -> Self {
+        self.default_depth = default_depth;
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Whether any symbol has a choice of productions, so different seeds
+    /// can grow different results.
+    pub fn is_stochastic(&self) -> bool {
+        self.productions.iter().enumerate().any(|(ix, production)| {
+            self.productions[ix + 1..]
+                .iter()
+                .any(|other| other.symbol == production.symbol)
+        })
+    }
+
+    /// The axiom rewritten `iterations` times, or as many times as fit in
+    /// [`MAX_MODULES`]. An iteration is abandoned as soon as it outgrows the
+    /// limit.
+    pub fn expand(&self, iterations: u32) -> Vec<Module> {
+        let mut rng = Rng::new(self.seed);
+        let mut current = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = Vec::with_capacity(current.len() * 2);
+            for module in current.iter() {
+                let candidates: Vec<_> = self
+                    .productions
+                    .iter()
+                    .filter(|production| production.applies_to(module))
+                    .collect();
+                let chosen = match candidates.len() {
+                    0 => None,
+                    1 => Some(candidates[0]),
+                    _ => rng
+                        .weighted(candidates.iter().map(|production| production.weight))
+                        .map(|ix| candidates[ix]),
+                };
+                match chosen {
+                    Some(production) => next.extend((production.successor)(&module.params)),
+                    None => next.push(module.clone()),
+                }
+                if next.len() > MAX_MODULES {
+                    return current;
+                }
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Walks the turtle over `modules` and returns every unbroken run of
+    /// drawn lines.
+    pub fn interpret(&self, modules: &[Module]) -> Vec<Branch> {
+        let mut position = Point::new(0.0f32, 0.0f32);
+        let mut heading = self.heading.to_radians();
+        let mut stack = Vec::new();
+        let mut branches = Vec::new();
+        let mut run = Vec::new();
+
+        for module in modules {
+            let depth = stack.len() as u32;
+            match module.symbol {
+                '+' => heading += module.param(0).unwrap_or(self.angle).to_radians(),
+                '-' => heading -= module.param(0).unwrap_or(self.angle).to_radians(),
+                '|' => heading += std::f32::consts::PI,
+                '[' => {
+                    end_run(&mut branches, &mut run, depth);
+                    stack.push((position, heading));
+                }
+                ']' => {
+                    if let Some((saved_position, saved_heading)) = stack.pop() {
+                        end_run(&mut branches, &mut run, depth);
+                        position = saved_position;
+                        heading = saved_heading;
+                    }
+                }
+                'f' => {
+                    end_run(&mut branches, &mut run, depth);
+                    position = forward(position, heading, module.param(0).unwrap_or(1.0));
+                }
+                symbol if self.draw_symbols.contains(symbol) => {
+                    if run.is_empty() {
+                        run.push(position);
+                    }
+                    position = forward(position, heading, module.param(0).unwrap_or(1.0));
+                    run.push(position);
+                }
+                _ => {}
+            }
+        }
+        end_run(&mut branches, &mut run, stack.len() as u32);
+        branches
+    }
+
+    /// The system expanded `iterations` times and drawn as polylines styled
+    /// by [`LSystem::style`], fitted to [`LSystem::bounds`].
+    pub fn shapes(&self, iterations: u32) -> Vec<Shape> {
+        let branches = self.interpret(&self.expand(iterations));
+        let deepest = branches
+            .iter()
+            .map(|branch| branch.depth)
+            .max()
+            .unwrap_or(0);
+        fit(&branches, self.bounds)
+            .into_iter()
+            .zip(&branches)
+            .map(|(points, branch)| {
+                let t = branch.depth as f32 / deepest.max(1) as f32;
+                let width = self.style.width * self.style.taper.powi(branch.depth as i32);
+                shapes::polyline(points)
+                    .stroke_width(width)
+                    .stroke_color(self.style.colors.sample(t))
+                    .line_join(LineJoin::Round)
+                    .line_cap(LineCap::Round)
+                    .into()
+            })
+            .collect()
+    }
+
+    pub fn koch_snowflake() -> LSystem {
+        LSystem::new("Koch snowflake L-system", "F--F--F", 60.0)
+            .rule('F', "F+F--F+F")
+            .depths(4, 7)
+    }
+
+    pub fn dragon_curve() -> LSystem {
+        LSystem::new("Dragon curve L-system", "FX", 90.0)
+            .rule('X', "X+YF+")
+            .rule('Y', "-FX-Y")
+            .depths(12, 18)
+    }
+
+    pub fn sierpinski_triangle() -> LSystem {
+        LSystem::new("Sierpinski L-system", "F-G-G", 120.0)
+            .rule('F', "F-G+F+G-F")
+            .rule('G', "GG")
+            .draw_symbols("FG")
+            .depths(6, 10)
+    }
+
+    /// A branching tree in the spirit of the Pythagoras tree.
+    pub fn binary_tree() -> LSystem {
+        LSystem::new("Binary tree", "X", 45.0)
+            .rule('X', "F[+X][-X]")
+            .rule('F', "FF")
+            .heading(90.0)
+            .depths(8, 14)
+    }
+
+    pub fn hilbert_curve() -> LSystem {
+        LSystem::new("Hilbert curve", "A", 90.0)
+            .rule('A', "+BF-AFA-FB+")
+            .rule('B', "-AF+BFB+FA-")
+            .depths(5, 10)
+    }
+
+    pub fn gosper_curve() -> LSystem {
+        LSystem::new("Gosper curve", "A", 60.0)
+            .rule('A', "A-B--B+A++AA+B-")
+            .rule('B', "+A-AA--A-B++B+A")
+            .draw_symbols("AB")
+            .depths(4, 7)
+    }
+
+    pub fn levy_c_curve() -> LSystem {
+        LSystem::new("Lévy C curve", "F", 45.0)
+            .rule('F', "+F--F+")
+            .depths(10, 18)
+    }
+
+    pub fn fractal_plant() -> LSystem {
+        LSystem::new("Fractal plant", "X", 25.0)
+            .rule('X', "F+[[X]-X]-F[-FX]+X")
+            .rule('F', "FF")
+            .heading(65.0)
+            .depths(5, 8)
+    }
+
+    /// Penrose's P3 rhombus tiling. Each `A` draws an edge and is dropped on
+    /// the next rewrite, leaving only the edges of the newest tiles.
+    pub fn penrose_tiling() -> LSystem {
+        LSystem::new("Penrose tiling", "[N]++[N]++[N]++[N]++[N]", 36.0)
+            .rule('M', "OA++PA----NA[-OA----MA]++")
+            .rule('N', "+OA--PA[---MA--NA]+")
+            .rule('O', "-MA++NA[+++OA++PA]-")
+            .rule('P', "--OA++++MA[+PA++++NA]--NA")
+            .rule('A', "")
+            .draw_symbols("A")
+            .depths(4, 7)
+    }
+
+    /// A weed that picks one of three branching patterns at every stem.
+    pub fn stochastic_plant() -> LSystem {
+        LSystem::new("Stochastic plant", "F", 25.7)
+            .stochastic_rule('F', 0.33, "F[+F]F[-F]F")
+            .stochastic_rule('F', 0.33, "F[+F]F")
+            .stochastic_rule('F', 0.34, "F[-F]F")
+            .heading(90.0)
+            .style(BranchStyle {
+                width: 3.0,
+                taper: 0.7,
+                colors: Palette::evenly_spaced(
+                    "Stem",
+                    [rgb(0x6d4c2f), rgb(0x558b2f), rgb(0xc5e1a5)],
+                ),
+            })
+            .depths(5, 7)
+    }
+
+    /// A tree whose branches shrink with every fork and stop once they are
+    /// too short. Each `A(length)` grows either a two- or a three-way fork.
+    pub fn parametric_tree() -> LSystem {
+        fn fork(params: &[f32], branches: &[(f32, f32)]) -> Vec<Module> {
+            let length = params.first().copied().unwrap_or(1.0);
+            let mut modules = vec![Module::new('F', [length])];
+            for &(angle, shrink) in branches {
+                modules.extend([
+                    Module::from('['),
+                    Module::new('+', [angle]),
+                    Module::new('A', [length * shrink]),
+                    Module::from(']'),
+                ]);
+            }
+            modules
+        }
+        let long_enough = |params: &[f32]| params.first().is_some_and(|length| *length >= 4.0);
+
+        LSystem::new("Parametric tree", "A(100)", 30.0)
+            .production(
+                Production::parametric('A', |params| fork(params, &[(28.0, 0.72), (-34.0, 0.66)]))
+                    .when(long_enough)
+                    .weight(0.6),
+            )
+            .production(
+                Production::parametric('A', |params| {
+                    fork(params, &[(40.0, 0.6), (-5.0, 0.75), (-42.0, 0.58)])
+                })
+                .when(long_enough)
+                .weight(0.4),
+            )
+            .heading(90.0)
+            .seed(7)
+            .style(BranchStyle {
+                width: 8.0,
+                taper: 0.68,
+                colors: Palette::evenly_spaced(
+                    "Bark to leaf",
+                    [rgb(0x4e342e), rgb(0x795548), rgb(0x7cb342), rgb(0xdce775)],
+                ),
+            })
+            .depths(9, 14)
+    }
+}
+
+impl fractal::Fractal for LSystem {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    fn default_depth(&self) -> u32 {
+        self.default_depth
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        self.shapes(depth).into()
+    }
+
+    /// Clicking a stochastic system grows it again from the next seed.
+    fn pick(&self, _position: Point<Pixels>) -> Option<Box<dyn fractal::Fractal>> {
+        self.is_stochastic().then(|| {
+            Box::new(self.clone().seed(self.seed.wrapping_add(1))) as Box<dyn fractal::Fractal>
+        })
+    }
+
+    /// The turning angle, in degrees.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![("angle", self.angle as f64)]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        if name != "angle" {
+            return false;
+        }
+        self.angle = value as f32;
+        true
+    }
+}
+
+/// Every preset, in the order a picker should list them.
+pub fn presets() -> Vec<LSystem> {
+    vec![
+        LSystem::koch_snowflake(),
+        LSystem::dragon_curve(),
+        LSystem::sierpinski_triangle(),
+        LSystem::binary_tree(),
+        LSystem::hilbert_curve(),
+        LSystem::gosper_curve(),
+        LSystem::levy_c_curve(),
+        LSystem::fractal_plant(),
+        LSystem::penrose_tiling(),
+        LSystem::stochastic_plant(),
+        LSystem::parametric_tree(),
+    ]
+}
+
+fn forward(position: Point<f32>, heading: f32, length: f32) -> Point<f32> {
+    // Screen y grows downwards, so counter-clockwise headings subtract.
+    Point::new(
+        position.x + heading.cos() * length,
+        position.y - heading.sin() * length,
+    )
+}
+
+fn end_run(branches: &mut Vec<Branch>, run: &mut Vec<Point<f32>>, depth: u32) {
+    if run.len() > 1 {
+        branches.push(Branch {
+            points: std::mem::take(run),
+            depth,
+        });
+    } else {
+        run.clear();
+    }
+}
+
+/// Scales and centers `branches` to fill `bounds` without distorting them.
+fn fit(branches: &[Branch], bounds: Bounds<Pixels>) -> Vec<Vec<Point<Pixels>>> {
+    let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+    let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for point in branches.iter().flat_map(|branch| &branch.points) {
+        min = Point::new(min.x.min(point.x), min.y.min(point.y));
+        max = Point::new(max.x.max(point.x), max.y.max(point.y));
+    }
+
+    let (width, height) = (bounds.size.width.0, bounds.size.height.0);
+    let extent = Point::new(max.x - min.x, max.y - min.y);
+    let scale = (width / extent.x.max(f32::EPSILON)).min(height / extent.y.max(f32::EPSILON));
+    let offset = Point::new(
+        bounds.origin.x.0 + (width - extent.x * scale) / 2.0,
+        bounds.origin.y.0 + (height - extent.y * scale) / 2.0,
+    );
+
+    branches
+        .iter()
+        .map(|branch| {
+            branch
+                .points
+                .iter()
+                .map(|p| {
+                    point(
+                        px(offset.x + (p.x - min.x) * scale),
+                        px(offset.y + (p.y - min.y) * scale),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(modules: &[Module]) -> String {
+        modules.iter().map(|module| module.symbol).collect()
+    }
+
+    #[test]
+    fn parse_reads_symbols_and_parameters() {
+        assert_eq!(
+            parse("F(1.5)+[A(100,8)]"),
+            vec![
+                Module::new('F', [1.5]),
+                Module::from('+'),
+                Module::from('['),
+                Module::new('A', [100.0, 8.0]),
+                Module::from(']'),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_skips_whitespace_and_drops_bad_parameters() {
+        assert_eq!(
+            parse(" F ( x , 2 ) f"),
+            vec![Module::new('F', [2.0]), Module::from('f')]
+        );
+        assert_eq!(parse("F()"), vec![Module::from('F')]);
+        assert!(parse("").is_empty());
+    }
+
+    #[test]
+    fn expand_rewrites_every_module_each_iteration() {
+        let algae = LSystem::new("Algae", "A", 0.0)
+            .rule('A', "AB")
+            .rule('B', "A");
+        assert_eq!(symbols(&algae.expand(0)), "A");
+        assert_eq!(symbols(&algae.expand(4)), "ABAABABA");
+    }
+
+    #[test]
+    fn expand_applies_parametric_productions_by_condition() {
+        let system = LSystem::new("Countdown", "A(3)", 0.0).production(
+            Production::parametric('A', |params| {
+                vec![Module::new('F', []), Module::new('A', [params[0] - 1.0])]
+            })
+            .when(|params| params[0] > 0.0),
+        );
+        assert_eq!(
+            system.expand(5),
+            parse("FFFA(0)"),
+            "A stops rewriting once its parameter reaches zero"
+        );
+    }
+
+    #[test]
+    fn expand_is_repeatable_for_a_seed() {
+        let plant = LSystem::stochastic_plant();
+        assert!(plant.is_stochastic());
+        assert_eq!(plant.expand(4), plant.expand(4));
+    }
+
+    #[test]
+    fn expand_stops_before_outgrowing_the_limit() {
+        let doubling = LSystem::new("Doubling", "F", 0.0).rule('F', "FF");
+        let modules = doubling.expand(30);
+        // One more doubling would pass the limit.
+        assert_eq!(modules.len(), 1 << 21);
+        assert!(modules.len() * 2 > MAX_MODULES);
+    }
+}
@@ -1,66 +1,214 @@
-#![allow(unused, dead_code)]
-
-use gpui::*;
-use std::{f32::consts::PI, time::Duration};
+use gpui::{prelude::FluentBuilder as _, *};
+use gpui_fractals::{
+    animation::{Keyframe, Repeat, Timeline, Track},
+    circular_sierpinski2::CircularCarpet,
+    dragon::DragonCurve,
+    escape_time::{self, RendererBuilder},
+    export, flame, fractal, ifs,
+    julia_set::JuliaSet,
+    koch::KochSnowflake,
+    lsystem,
+    mandelbrot::Mandelbrot,
+    newton,
+    palette::Palette,
+    plot::Plot,
+    pythagoras::PythagorasTree,
+    raster::MAX_QUAD_PIXELS,
+    shapes,
+    sierpinski::SierpinskiTriangle,
+    viewport::Viewport,
+};
+use std::{
+    f32::consts::PI,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+actions!(
+    fractal_viewer,
+    [
+        SelectNext,
+        SelectPrevious,
+        NextPalette,
+        NextColorMapping,
+        TogglePlayback,
+        NextRepeat,
+        AddKeyframe,
+        ClearKeyframes,
+        ExportImage
+    ]
+);
+
+/// The formula animation's epochs, from start to end.
+const FORMULA_EPOCHS: u64 = 512;
+
+/// One epoch every 8ms.
+const EPOCHS_PER_SECOND: f64 = 125.0;
+
+const FORMULA_DURATION: f64 = FORMULA_EPOCHS as f64 / EPOCHS_PER_SECOND;
+
+/// Seconds between keyframes added from the viewer.
+const KEYFRAME_SPACING: f64 = 2.0;
+
+/// How many steps a formula slider is dragged through.
+const SLIDER_STEPS: usize = 24;
 
 struct ColoredPoint {
     position: Point<Pixels>,
     color: gpui::Hsla,
 }
 
+/// A formula of the plot being typed into.
+struct Editing {
+    /// Index into `Plot::formulas`.
+    formula: usize,
+    /// What the formula said before, for escape to put back.
+    original: String,
+}
+
 struct FractalViewer {
+    focus_handle: FocusHandle,
+    fractals: Vec<Box<dyn fractal::Fractal>>,
+    /// Index into `fractals`, or `None` while showing the radial formula animation.
+    active: Option<usize>,
+    /// Where the current drag started and where its last event was, while the
+    /// canvas is being dragged.
+    drag_origin: Option<Point<Pixels>>,
+    drag_position: Option<Point<Pixels>>,
     quads: Vec<gpui::PaintQuad>,
     temp_quads: Vec<gpui::PaintQuad>,
     paths: Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
+    /// Images and where to draw them: a whole raster, or the tiles of one
+    /// rendered in the background.
+    images: Vec<(Bounds<Pixels>, Arc<RenderImage>)>,
+    render_task: Option<Task<()>>,
+    /// Whether the tiles of the last regeneration are still rendering.
+    rendering: bool,
     formula_points: Vec<ColoredPoint>,
+    /// The formulas behind `formula_points`, as typed in.
+    plot: Plot,
+    editing: Option<Editing>,
     epoch: u64,
+    /// The timeline cycle the formula's trails were last cleared in.
+    formula_cycle: u64,
+    /// Plays the formula animation, or the active fractal's keyframes.
+    timeline: Timeline,
+    /// When the last animation frame was drawn, while the timeline plays.
+    last_frame: Option<Instant>,
+    /// Whether a tick is waiting for the next frame, so starting playback
+    /// again doesn't start a second chain of ticks.
+    tick_scheduled: bool,
+    /// Keyframes for each of `fractals`, by index.
+    tracks: Vec<Track>,
+    /// Where the last export went, or why it failed, until another fractal
+    /// is picked.
+    notice: Option<String>,
 }
 
 impl FractalViewer {
-    fn new(cx: &mut Context<Self>) -> Self {
-        cx.spawn(|this, mut cx| async move {
-            loop {
-                Timer::after(Duration::from_millis(8)).await;
-                this.update(&mut cx, |this, cx| {
-                    this.update_epoch(cx);
-                })
-                .ok();
-            }
-        })
-        .detach();
-
-        Self {
+    fn new(
+        fractals: Vec<Box<dyn fractal::Fractal>>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let focus_handle = cx.focus_handle();
+        window.focus(&focus_handle);
+        let tracks = fractals.iter().map(|_| Track::default()).collect();
+
+        let mut viewer = Self {
+            focus_handle,
+            fractals,
+            active: None,
+            drag_origin: None,
+            drag_position: None,
             quads: vec![],
             temp_quads: vec![],
             paths: vec![],
+            images: vec![],
+            render_task: None,
+            rendering: false,
             formula_points: Vec::new(),
+            plot: Plot::default(),
+            editing: None,
             epoch: 0,
+            formula_cycle: 0,
+            timeline: Timeline::new(FORMULA_DURATION, Repeat::PingPong),
+            last_frame: None,
+            tick_scheduled: false,
+            tracks,
+            notice: None,
+        };
+        viewer.schedule_tick(window, cx);
+        viewer
+    }
+
+    /// Whether the timeline has anything to play and is playing it, so the
+    /// window should keep drawing frames.
+    fn is_animating(&self) -> bool {
+        self.timeline.is_playing() && self.timeline.duration > 0.0
+    }
+
+    /// Ticks on the next frame, unless the timeline has nothing to play or a
+    /// tick is already waiting.
+    fn schedule_tick(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.tick_scheduled || !self.is_animating() {
+            return;
         }
+        self.tick_scheduled = true;
+        cx.on_next_frame(window, |this, window, cx| {
+            this.tick_scheduled = false;
+            this.tick(window, cx);
+        });
     }
-    fn update_epoch(&mut self, cx: &mut Context<Self>) {
-        const START_EPOCH: u64 = 0;
-        const MAX_EPOCH: u64 = 512;
-        let mut max_cycles = 1;
-
-        if self.epoch == MAX_EPOCH - 1 {
-            max_cycles -= 1;
-            if max_cycles == 0 {
-                return;
+
+    /// Moves the timeline on by the wall-clock time since the last frame,
+    /// shows the frame it lands on and schedules the next tick. Once the
+    /// timeline is paused or finished no further tick is scheduled, so the
+    /// window goes idle.
+    fn tick(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if !self.is_animating() {
+            self.last_frame = None;
+            return;
+        }
+        let now = Instant::now();
+        let elapsed = self.last_frame.map_or(Duration::ZERO, |last| now - last);
+        self.last_frame = Some(now);
+        self.timeline.advance(elapsed);
+        cx.notify();
+
+        // Let the last frame's tiles finish instead of cancelling them every
+        // frame. The timeline keeps moving meanwhile, so slow frames get
+        // dropped rather than slowing playback down.
+        if !(self.active.is_some() && self.rendering && self.timeline.is_playing()) {
+            self.show_frame(window, cx);
+        }
+        self.schedule_tick(window, cx);
+    }
+
+    /// Draws the active entry at the timeline's current time.
+    fn show_frame(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        match self.active {
+            None => self.update_epoch(cx),
+            Some(ix) => {
+                self.tracks[ix].apply(self.timeline.time(), self.fractals[ix].as_mut());
+                self.regenerate(window, cx);
             }
         }
+    }
 
-        let direction = if (self.epoch / MAX_EPOCH) % 2 == 0 {
-            1
-        } else {
-            -1
-        };
-        self.epoch = (self.epoch as i64 + direction).rem_euclid(MAX_EPOCH as i64) as u64;
+    fn update_epoch(&mut self, cx: &mut Context<Self>) {
+        let previous = self.epoch;
+        self.epoch = ((self.timeline.time() * EPOCHS_PER_SECOND) as u64).min(FORMULA_EPOCHS - 1);
+        // Every eighth epoch leaves its points behind as a trail.
+        let keep = self.epoch / 8 != previous / 8;
 
-        if self.epoch == START_EPOCH {
+        let cycle = self.timeline.cycle();
+        if cycle != self.formula_cycle {
             // Clear all quads at the start of each cycle
+            self.formula_cycle = cycle;
             self.quads.clear();
             self.temp_quads.clear();
-        } else if self.epoch % 8 == 0 {
+        } else if keep {
             // Keep the current quads and add them to the permanent set
             self.quads.append(&mut self.temp_quads);
         } else {
@@ -75,44 +223,11 @@ impl FractalViewer {
         let num_points = 16;
         let angle_step = 2.0 * PI / num_points as f32;
 
-        self.generate_formula_points(
-            |t| {
-                let mut angle = t * angle_step + (epoch as f32 / 128.0);
-
-                // Rotate points on odd epochs
-                if epoch % 2.0 != 0.0 {
-                    angle += PI;
-                }
-
-                // Add some chaotic variation to the radius
-                let chaotic_factor = (epoch * t).sin() * 0.2;
-                let base_radius = radius * (1.0 + 0.5 * (epoch / 128.0).sin() + chaotic_factor);
-
-                // Apply a spiraling effect
-                let spiral_factor = (epoch / 32.0) * 0.1;
-                let x = center_x + base_radius * (angle + spiral_factor).cos();
-                let y = center_y + base_radius * (angle + spiral_factor).sin();
-
-                // Calculate color based on angle and epoch with more variation
-                let hue = (t + (epoch / 64.0).sin()) % 1.0;
-                let saturation =
-                    0.5 + 0.5 * ((epoch / MAX_EPOCH as f32).sin() * (t * 2.0 * PI).cos());
-                let lightness = 0.5 + 0.3 * ((epoch / 64.0).cos() * (t * 3.0 * PI).sin());
-
-                let color = gpui::hsla(hue, saturation, lightness, 1.0);
-
-                ColoredPoint {
-                    position: point(px(x), px(y)),
-                    color,
-                }
-            },
-            (0.0, num_points as f32),
-            1.0,
-        );
+        self.generate_formula_points();
 
         // Add lines casting from the radial shape
         for i in 0..num_points {
-            let angle = i as f32 * angle_step + (epoch as f32 / 75.0);
+            let angle = i as f32 * angle_step + (epoch / 75.0);
             let base_radius = radius * (1.0 + 0.5 * (epoch / 128.0).sin()); // Increased variation
             let start_x = center_x + base_radius * angle.cos();
             let start_y = center_y + base_radius * angle.sin();
@@ -136,573 +251,1035 @@ impl FractalViewer {
             }
         }
 
-        self.draw_formula();
+        self.draw_formula(keep);
         cx.notify();
     }
 
-    fn generate_formula_points(
-        &mut self,
-        formula: impl Fn(f32) -> ColoredPoint,
-        range: (f32, f32),
-        step: f32,
-    ) {
+    /// Evaluates the typed-in formulas at the current epoch.
+    fn generate_formula_points(&mut self) {
         self.formula_points.clear();
-        let (start, end) = range;
-        let mut t = start;
-        while t <= end {
-            let colored_point = formula(t);
-            self.formula_points.push(colored_point);
-            t += step;
+        for plotted in self.plot.points(self.epoch as f64) {
+            self.formula_points.push(ColoredPoint {
+                position: point(px(plotted.x as f32), px(plotted.y as f32)),
+                color: gpui::hsla(
+                    plotted.hue as f32,
+                    plotted.saturation as f32,
+                    plotted.lightness as f32,
+                    1.0,
+                ),
+            });
         }
     }
 
-    fn draw_formula(&mut self) {
-        for colored_point in self.formula_points.iter() {
-            let quad = shapes::pixel(colored_point.position)
-                .color(colored_point.color)
-                .quad();
-            if self.epoch % 8 == 0 {
-                self.quads.push(quad);
-            } else {
-                self.temp_quads.push(quad);
-            }
+    /// Redraws the formula animation from scratch after the formulas or
+    /// sliders change, even while it is paused.
+    fn replot(&mut self, cx: &mut Context<Self>) {
+        if self.active.is_some() {
+            return;
         }
+        self.quads.clear();
+        self.temp_quads.clear();
+        self.update_epoch(cx);
     }
-}
-
-impl Render for FractalViewer {
-    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
-        let mut all_quads = self.quads.clone();
-        all_quads.extend(self.temp_quads.clone());
-        let paths = self.paths.clone();
 
-        div()
-            .bg(gpui::black())
-            .size_full()
-            .child(render_canvas(all_quads, paths, window, cx))
+    /// Starts typing into formula `ix` of the plot.
+    fn edit_formula(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let original = self.plot.formulas()[ix].source.clone();
+        self.editing = Some(Editing {
+            formula: ix,
+            original,
+        });
+        cx.notify();
     }
-}
 
-fn render_canvas(
-    quads: Vec<gpui::PaintQuad>,
-    paths: Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
-    _window: &mut Window,
-    _cx: &mut App,
-) -> impl IntoElement {
-    canvas(
-        |_, _, _| {},
-        move |_, _, window, _| {
-            for quad in quads.iter() {
-                window.paint_quad(quad.clone());
+    /// Types into the formula being edited, replotting as it goes. Enter keeps
+    /// the edit and escape puts back what was there before.
+    fn type_key(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(editing) = &self.editing else {
+            return;
+        };
+        let ix = editing.formula;
+        let mut source = self.plot.formulas()[ix].source.clone();
+        let keystroke = &event.keystroke;
+        match keystroke.key.as_str() {
+            "enter" => self.editing = None,
+            "escape" => {
+                source = editing.original.clone();
+                self.editing = None;
             }
-            for (path, color) in paths.iter() {
-                window.paint_path(path.clone(), *color);
+            "backspace" => {
+                source.pop();
             }
-        },
-    )
-    .size_full()
-}
-
-fn main() {
-    Application::new().run(|cx| {
-        cx.open_window(
-            WindowOptions {
-                titlebar: Some(TitlebarOptions {
-                    title: Some("gpui".into()),
-                    ..Default::default()
-                }),
-                window_bounds: Some(gpui::WindowBounds::Windowed(Bounds::new(
-                    point(px(0.), px(0.)),
-                    Size::new(px(768.), px(768.)),
-                ))),
-                focus: true,
-                ..Default::default()
+            _ => match &keystroke.key_char {
+                Some(typed) if !keystroke.modifiers.control && !keystroke.modifiers.platform => {
+                    source.push_str(typed)
+                }
+                _ => return,
             },
-            |_, cx| cx.new(|cx| FractalViewer::new(cx)),
-        )
-        .unwrap();
-        cx.activate(true);
-    });
-}
-
-mod shapes {
-
-    use super::*;
-
-    pub struct Stroke {
-        width: Pixels,
-        color: gpui::Hsla,
+        }
+        self.plot.set_source(ix, source);
+        self.replot(cx);
+        cx.notify();
     }
 
-    pub struct ShapeProperties {
-        fill: Background,
-        position: Point<Pixels>,
-        size: Pixels,
-        stroke: Stroke,
+    fn set_slider(&mut self, ix: usize, fraction: f64, cx: &mut Context<Self>) {
+        let slider = &mut self.plot.sliders[ix];
+        let value = slider.value;
+        slider.set_fraction(fraction);
+        if slider.value != value {
+            self.replot(cx);
+        }
     }
 
-    impl From<Hsla> for Stroke {
-        fn from(color: Hsla) -> Self {
-            Stroke {
-                width: px(1.),
-                color,
+    fn draw_formula(&mut self, keep: bool) {
+        for colored_point in self.formula_points.iter() {
+            let quad = shapes::pixel(colored_point.position)
+                .color(colored_point.color)
+                .quad();
+            if keep {
+                self.quads.push(quad);
+            } else {
+                self.temp_quads.push(quad);
             }
         }
     }
 
-    pub fn circle(radius: impl Into<Pixels>, position: Point<Pixels>) -> Circle {
-        Circle::new(radius.into(), position)
+    fn select(&mut self, active: Option<usize>, window: &mut Window, cx: &mut Context<Self>) {
+        self.active = active;
+        self.editing = None;
+        self.notice = None;
+        self.epoch = 0;
+        self.formula_cycle = 0;
+        let duration = match active {
+            Some(ix) => self.tracks[ix].duration(),
+            None => FORMULA_DURATION,
+        };
+        self.timeline = Timeline::new(duration, self.timeline.repeat);
+        self.schedule_tick(window, cx);
+        self.quads.clear();
+        self.temp_quads.clear();
+        self.paths.clear();
+        self.formula_points.clear();
+        self.clear_images(window);
+        self.regenerate(window, cx);
     }
 
-    pub fn pixel(position: Point<Pixels>) -> Pixel {
-        Pixel::new(position)
-    }
+    fn regenerate(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.render_task = None;
+        self.rendering = false;
 
-    pub fn line(start: Point<Pixels>, end: Point<Pixels>) -> Line {
-        Line::new(start, end)
+        if let Some(ix) = self.active {
+            let depth = self.depth(ix);
+            let fractal = &self.fractals[ix];
+            if let Some(build) = fractal.tile_renderer(depth) {
+                // Leave the previous tiles up until their replacements arrive.
+                self.render_tiles(build, window, cx);
+            } else {
+                let output = fractal.generate(depth);
+                self.quads.clear();
+                self.paths.clear();
+                for shape in &output.shapes {
+                    match shape {
+                        // A quad paints a circle far cheaper than its
+                        // tessellated outline.
+                        shapes::Shape::Circle(circle) => self.quads.push(circle.quad()),
+                        shape => self.paths.extend(shape.paint()),
+                    }
+                }
+                self.clear_images(window);
+                if let Some(raster) = output.image {
+                    if raster.width * raster.height <= MAX_QUAD_PIXELS {
+                        self.quads.extend(raster.quads());
+                    } else {
+                        let bounds = Bounds::new(
+                            point(px(0.), px(0.)),
+                            Size::new(px(raster.width as f32), px(raster.height as f32)),
+                        );
+                        self.images.push((bounds, raster.render_image()));
+                    }
+                }
+            }
+        }
+        cx.notify();
     }
 
-    pub fn triangle(p1: Point<Pixels>, p2: Point<Pixels>, p3: Point<Pixels>) -> Triangle {
-        Triangle::new(p1, p2, p3)
+    /// The depth fractal `ix`'s keyframes set at the current time, or its
+    /// default.
+    fn depth(&self, ix: usize) -> u32 {
+        self.tracks[ix]
+            .sample(self.timeline.time())
+            .and_then(|keyframe| keyframe.depth)
+            .unwrap_or_else(|| self.fractals[ix].default_depth())
     }
 
-    pub struct Circle {
-        fill: gpui::Background,
-        position: Point<Pixels>,
-        size: Pixels,
-        stroke: Stroke,
-    }
+    /// Sets up the renderer `build` makes and renders every tile, all on gpui's
+    /// background executor, and swaps each tile in as soon as it is done.
+    /// Replacing `render_task` cancels whatever is still pending.
+    fn render_tiles(
+        &mut self,
+        build: RendererBuilder,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.rendering = true;
+        self.render_task = Some(cx.spawn_in(window, |this, mut cx| async move {
+            let renderer = cx.background_executor().spawn(async move { build() }).await;
+            let (width, height) = renderer.size();
+            let pending = escape_time::tiles(width, height, escape_time::TILE_SIZE)
+                .into_iter()
+                .map(|tile| {
+                    let renderer = renderer.clone();
+                    cx.background_executor().spawn(async move {
+                        (tile.bounds(), renderer.render_tile(tile).render_image())
+                    })
+                })
+                .collect::<Vec<_>>();
 
-    pub struct Pixel {
-        position: Point<Pixels>,
-        color: gpui::Hsla,
+            for task in pending {
+                let (bounds, image) = task.await;
+                this.update_in(&mut cx, |this, window, cx| {
+                    this.show_image(bounds, image, window);
+                    cx.notify();
+                })
+                .ok();
+            }
+            this.update_in(&mut cx, |this, _, _| this.rendering = false)
+                .ok();
+        }));
     }
 
-    pub struct Line {
-        start: Point<Pixels>,
-        end: Point<Pixels>,
-        stroke: Stroke,
+    fn show_image(&mut self, bounds: Bounds<Pixels>, image: Arc<RenderImage>, window: &mut Window) {
+        match self
+            .images
+            .iter_mut()
+            .find(|(existing, _)| *existing == bounds)
+        {
+            Some((_, existing)) => {
+                let old = std::mem::replace(existing, image);
+                window.drop_image(old).ok();
+            }
+            None => self.images.push((bounds, image)),
+        }
     }
 
-    pub struct Triangle {
-        p1: Point<Pixels>,
-        p2: Point<Pixels>,
-        p3: Point<Pixels>,
-        stroke: Stroke,
+    /// Every image is a separate texture, so release them as they go away.
+    fn clear_images(&mut self, window: &mut Window) {
+        for (_, image) in self.images.drain(..) {
+            window.drop_image(image).ok();
+        }
     }
 
-    impl Circle {
-        pub fn new(radius: Pixels, position: Point<Pixels>) -> Self {
-            let stroke: Stroke = gpui::white().into();
-            let fill: gpui::Background = gpui::transparent_black().into();
+    fn active_viewport(&mut self) -> Option<&mut Viewport> {
+        let ix = self.active?;
+        self.fractals[ix].viewport_mut()
+    }
 
-            Circle {
-                stroke,
-                fill,
-                size: radius * 2.0,
-                position,
-            }
-        }
+    fn zoom(&mut self, event: &ScrollWheelEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let delta = event.delta.pixel_delta(px(20.)).y;
+        let Some(viewport) = self.active_viewport() else {
+            return;
+        };
+        // Each 256px of scrolling doubles (or halves) the magnification.
+        let factor = 2f64.powf(delta.0 as f64 / 256.0);
+        viewport.zoom_at(event.position.x.0 as f64, event.position.y.0 as f64, factor);
+        self.regenerate(window, cx);
+    }
 
-        pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
-            self.stroke.width = width.into();
-            self
-        }
+    fn start_drag(&mut self, event: &MouseDownEvent, _: &mut Window, _: &mut Context<Self>) {
+        self.drag_origin = Some(event.position);
+        self.drag_position = Some(event.position);
+    }
 
-        pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
-            self.stroke.color = color.into();
-            self
+    fn drag(&mut self, event: &MouseMoveEvent, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(last_position) = self.drag_position else {
+            return;
+        };
+        if event.pressed_button != Some(MouseButton::Left) {
+            self.drag_position = None;
+            return;
         }
+        self.drag_position = Some(event.position);
 
-        pub fn fill(mut self, fill: impl Into<gpui::Background>) -> Self {
-            self.fill = fill.into();
-            self
-        }
+        let delta = event.position - last_position;
+        let Some(viewport) = self.active_viewport() else {
+            return;
+        };
+        viewport.pan(delta.x.0 as f64, delta.y.0 as f64);
+        self.regenerate(window, cx);
+    }
 
-        pub fn no_stroke(mut self) -> Self {
-            self.stroke = Stroke {
-                width: px(0.),
-                color: gpui::transparent_black(),
-            };
-            self
-        }
+    fn end_drag(&mut self, event: &MouseUpEvent, window: &mut Window, cx: &mut Context<Self>) {
+        self.drag_position = None;
+        let Some(origin) = self.drag_origin.take() else {
+            return;
+        };
 
-        pub fn quad(&self) -> gpui::PaintQuad {
-            let center = point(self.position.x, self.position.y);
-
-            let bounds = Bounds::centered_at(
-                center,
-                Size {
-                    width: self.size,
-                    height: self.size,
-                },
-            );
-            let background = self.fill;
-            let border_color = self.stroke.color;
-            let border_width = self.stroke.width;
-            let corner_radii = Corners::all(self.size / 2.0);
-
-            gpui::PaintQuad {
-                bounds,
-                corner_radii,
-                background,
-                border_widths: Edges::all(border_width),
-                border_color,
-            }
+        // A press and release that barely moved is a click, not a pan.
+        let moved = event.position - origin;
+        if moved.x.0.abs() < 3.0 && moved.y.0.abs() < 3.0 {
+            self.pick(event.position, window, cx);
         }
     }
 
-    impl Pixel {
-        pub fn new(position: Point<Pixels>) -> Self {
-            Pixel {
-                position,
-                color: gpui::white(),
-            }
-        }
-
-        pub fn color(mut self, color: impl Into<gpui::Hsla>) -> Self {
-            self.color = color.into();
-            self
-        }
+    /// Lets the active fractal open another one for a clicked point, replacing
+    /// the picker entry of the same name.
+    fn pick(&mut self, position: Point<Pixels>, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(picked) = self.active.and_then(|ix| self.fractals[ix].pick(position)) else {
+            return;
+        };
 
-        pub fn quad(&self) -> gpui::PaintQuad {
-            let bounds = Bounds::from_corner_and_size(
-                gpui::Corner::TopLeft,
-                self.position,
-                Size {
-                    width: px(1.),
-                    height: px(1.),
-                },
-            );
-            let background = self.color.into();
-
-            gpui::PaintQuad {
-                bounds,
-                corner_radii: Corners::default(),
-                background,
-                border_widths: Edges::default(),
-                border_color: gpui::transparent_black(),
+        let ix = match self
+            .fractals
+            .iter()
+            .position(|fractal| fractal.name() == picked.name())
+        {
+            Some(ix) => {
+                self.fractals[ix] = picked;
+                self.tracks[ix] = Track::default();
+                ix
             }
-        }
-    }
-
-    impl Line {
-        pub fn new(start: Point<Pixels>, end: Point<Pixels>) -> Self {
-            Line {
-                start,
-                end,
-                stroke: gpui::white().into(),
+            None => {
+                self.fractals.push(picked);
+                self.tracks.push(Track::default());
+                self.fractals.len() - 1
             }
-        }
-
-        pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
-            self.stroke.width = width.into();
-            self
-        }
-
-        pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
-            self.stroke.color = color.into();
-            self
-        }
+        };
+        self.select(Some(ix), window, cx);
+    }
 
-        pub fn paint(&self) -> (gpui::Path<Pixels>, gpui::Hsla) {
-            let mut path = Path::new(self.start);
-            path.line_to(self.end);
-            (path, self.stroke.color)
-        }
+    /// Picker entries in display order: the formula animation, then every fractal.
+    fn entries(&self) -> impl Iterator<Item = Option<usize>> {
+        std::iter::once(None).chain((0..self.fractals.len()).map(Some))
     }
 
-    impl Triangle {
-        pub fn new(p1: Point<Pixels>, p2: Point<Pixels>, p3: Point<Pixels>) -> Self {
-            Triangle {
-                p1,
-                p2,
-                p3,
-                stroke: gpui::white().into(),
-            }
-        }
+    fn select_next(&mut self, _: &SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        let next = match self.active {
+            None if !self.fractals.is_empty() => Some(0),
+            Some(ix) if ix + 1 < self.fractals.len() => Some(ix + 1),
+            _ => None,
+        };
+        self.select(next, window, cx);
+    }
 
-        pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
-            self.stroke.width = width.into();
-            self
-        }
+    fn select_previous(&mut self, _: &SelectPrevious, window: &mut Window, cx: &mut Context<Self>) {
+        let previous = match self.active {
+            None => self.fractals.len().checked_sub(1),
+            Some(0) => None,
+            Some(ix) => Some(ix - 1),
+        };
+        self.select(previous, window, cx);
+    }
 
-        pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
-            self.stroke.color = color.into();
-            self
-        }
+    fn next_palette(&mut self, _: &NextPalette, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        let Some(palette) = self.fractals[ix].palette_mut() else {
+            return;
+        };
+        *palette = palette.next_builtin();
+        self.regenerate(window, cx);
+    }
 
-        pub fn paint(&self) -> (gpui::Path<Pixels>, gpui::Hsla) {
-            let mut path = Path::new(self.p1);
-            path.line_to(self.p2);
-            path.line_to(self.p3);
-            path.line_to(self.p1);
-            (path, self.stroke.color)
-        }
+    fn next_color_mapping(
+        &mut self,
+        _: &NextColorMapping,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        let Some(coloring) = self.fractals[ix].coloring_mut() else {
+            return;
+        };
+        coloring.mapping = coloring.mapping.next();
+        self.regenerate(window, cx);
     }
-}
 
-mod julia_set {
-    use super::*;
-    use gpui::hsla;
-    use num_complex::Complex;
-
-    pub fn generate(
-        width: usize,
-        height: usize,
-        c: Complex<f32>,
-        max_iterations: u32,
-    ) -> Vec<gpui::PaintQuad> {
-        let mut quads = Vec::new();
-
-        for y in 0..height {
-            for x in 0..width {
-                let scaled_x = (x as f32 / width as f32) * 4.0 - 2.0;
-                let scaled_y = (y as f32 / height as f32) * 4.0 - 2.0;
-                let mut z = Complex::new(scaled_x, scaled_y);
-
-                let mut i = 0;
-                while i < max_iterations && z.norm() <= 2.0 {
-                    z = z * z + c;
-                    i += 1;
-                }
+    /// Nudges one of the active fractal's parameters up or down a step.
+    fn adjust_parameter(
+        &mut self,
+        name: &'static str,
+        direction: f64,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        let fractal = &mut self.fractals[ix];
+        let Some((_, value)) = fractal.parameters().into_iter().find(|(n, _)| *n == name) else {
+            return;
+        };
+        fractal.set_parameter(name, value + direction * parameter_step(value));
+        self.regenerate(window, cx);
+    }
 
-                if i < max_iterations {
-                    let color = hsla((i as f32 / max_iterations as f32) * 360.0, 100.0, 50.0, 1.0);
-                    let pixel = shapes::pixel(point(px(x as f32), px(y as f32))).color(color);
-                    quads.push(pixel.quad());
-                }
-            }
-        }
+    fn toggle_playback(&mut self, _: &TogglePlayback, window: &mut Window, cx: &mut Context<Self>) {
+        self.timeline.toggle();
+        self.schedule_tick(window, cx);
+        cx.notify();
+    }
 
-        quads
+    fn next_repeat(&mut self, _: &NextRepeat, _: &mut Window, cx: &mut Context<Self>) {
+        self.timeline.repeat = self.timeline.repeat.next();
+        cx.notify();
     }
-}
 
-mod circular_sierpinski2 {
-    use super::*;
+    /// Records the active fractal's view and parameters as a keyframe after
+    /// the last one, and parks the playhead on it.
+    fn add_keyframe(&mut self, _: &AddKeyframe, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        let depth = self.depth(ix);
+        let track = &mut self.tracks[ix];
+        let time = track
+            .keyframes
+            .last()
+            .map_or(0.0, |last| last.time + KEYFRAME_SPACING);
+        track
+            .keyframes
+            .push(Keyframe::capture(time, depth, self.fractals[ix].as_mut()));
+        self.timeline.duration = track.duration();
+        self.timeline.pause();
+        self.timeline.seek(time);
+        cx.notify();
+    }
 
-    pub fn carpet(
-        center: Point<Pixels>,
-        radius: Pixels,
-        depth: u32,
-        angle: f32,
-    ) -> Vec<gpui::PaintQuad> {
-        recursive(center, radius, depth, angle)
+    /// Writes the active fractal, as it is shown now, to the first free
+    /// `<name>-N.png` in the working directory, at the size of the window.
+    fn export_image(&mut self, _: &ExportImage, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        let depth = self.depth(ix);
+        let size = window.viewport_size();
+        let fractal = self.fractals[ix].as_mut();
+        let name = slug(fractal.name());
+        let path = (1..)
+            .map(|n| format!("{name}-{n}.png"))
+            .find(|path| !std::path::Path::new(path).exists())
+            .expect("some file name is free");
+        let result = export::png(
+            fractal,
+            depth,
+            size.width.0 as usize,
+            size.height.0 as usize,
+            &path,
+        );
+        self.notice = Some(match result {
+            Ok(()) => format!("Saved {path}"),
+            Err(error) => error.to_string(),
+        });
+        cx.notify();
     }
 
-    fn recursive(
-        center: Point<Pixels>,
-        radius: Pixels,
-        depth: u32,
-        angle: f32,
-    ) -> Vec<gpui::PaintQuad> {
-        let mut quads = Vec::new();
+    fn clear_keyframes(&mut self, _: &ClearKeyframes, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.active else {
+            return;
+        };
+        self.tracks[ix] = Track::default();
+        self.timeline.duration = 0.0;
+        self.timeline.seek(0.0);
+        cx.notify();
+    }
 
-        if depth == 0 {
-            return quads;
+    /// Moves the playhead to the scrub bar position under `x`, which spans the
+    /// window, and shows that frame.
+    fn scrub(&mut self, x: Pixels, window: &mut Window, cx: &mut Context<Self>) {
+        if self.timeline.duration <= 0.0 {
+            return;
         }
-
-        quads.push(shapes::circle(radius, center).quad());
-
-        let inner_radius = radius / 3.0;
-        let offset = radius * 2.0 / 3.0;
-
-        for i in 0..8 {
-            let circle_angle = i as f32 * PI / 4.0 + angle;
-            let x = center.x + px(offset.0 * circle_angle.cos());
-            let y = center.y + px(offset.0 * circle_angle.sin());
-            quads.extend(recursive(Point { x, y }, inner_radius, depth - 1, angle));
+        let fraction = (x.0 / window.viewport_size().width.0).clamp(0.0, 1.0);
+        self.timeline.seek(fraction as f64 * self.timeline.duration);
+        if self.active.is_none() {
+            // Trails only make sense when the formula plays continuously.
+            self.quads.clear();
+            self.temp_quads.clear();
         }
+        self.show_frame(window, cx);
+    }
 
-        quads.extend(recursive(center, inner_radius, depth - 1, angle));
+    fn render_picker(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let coloring = self.active.and_then(|ix| {
+            let fractal = &self.fractals[ix];
+            match fractal.coloring() {
+                Some(coloring) => Some(format!(
+                    "{} / {} (p / m)",
+                    coloring.palette.name,
+                    coloring.mapping.name()
+                )),
+                None => Some(format!("{} (p)", fractal.palette()?.name)),
+            }
+        });
+        let parameters = self
+            .active
+            .map(|ix| self.fractals[ix].parameters())
+            .unwrap_or_default();
 
-        quads
+        div()
+            .absolute()
+            .top_2()
+            .left_2()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .rounded_md()
+            .occlude()
+            .bg(gpui::black().opacity(0.7))
+            .text_color(gpui::white())
+            .text_sm()
+            .children(self.entries().enumerate().map(|(ix, entry)| {
+                let name = match entry {
+                    Some(fractal_ix) => self.fractals[fractal_ix].name(),
+                    None => "Formula",
+                };
+                div()
+                    .id(ix)
+                    .px_2()
+                    .py_1()
+                    .rounded_sm()
+                    .cursor_pointer()
+                    .hover(|style| style.bg(gpui::white().opacity(0.1)))
+                    .when(entry == self.active, |this| {
+                        this.bg(gpui::white().opacity(0.2))
+                    })
+                    .on_click(
+                        cx.listener(move |this, _, window, cx| this.select(entry, window, cx)),
+                    )
+                    .child(name)
+            }))
+            .when_some(coloring, |this, coloring| {
+                this.child(
+                    div()
+                        .px_2()
+                        .pt_1()
+                        .text_color(gpui::white().opacity(0.6))
+                        .child(coloring),
+                )
+            })
+            .when_some(self.notice.clone(), |this, notice| {
+                this.child(
+                    div()
+                        .px_2()
+                        .text_color(gpui::white().opacity(0.6))
+                        .child(notice),
+                )
+            })
+            .children(parameters.into_iter().map(|(name, value)| {
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .px_2()
+                    .child(div().flex_1().child(format!("{name} {value:.3}")))
+                    .child(
+                        button(ElementId::Name(format!("{name}-down").into()), "-").on_click(
+                            cx.listener(move |this, _, window, cx| {
+                                this.adjust_parameter(name, -1.0, window, cx)
+                            }),
+                        ),
+                    )
+                    .child(
+                        button(ElementId::Name(format!("{name}-up").into()), "+").on_click(
+                            cx.listener(move |this, _, window, cx| {
+                                this.adjust_parameter(name, 1.0, window, cx)
+                            }),
+                        ),
+                    )
+            }))
     }
-}
 
-mod dragon {
-    use super::*;
-
-    pub fn curve(
-        start: Point<Pixels>,
-        end: Point<Pixels>,
-        iterations: u32,
-    ) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
-        let mut paths = Vec::new();
-        recursive(&mut paths, start, end, iterations, true);
-        paths
-    }
-
-    fn recursive(
-        paths: &mut Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
-        start: Point<Pixels>,
-        end: Point<Pixels>,
-        iterations: u32,
-        is_right: bool,
-    ) {
-        if iterations == 0 {
-            paths.push(shapes::line(start, end).paint());
-        } else {
-            let mid = Point {
-                x: (start.x + end.x) / 2.0
-                    + (end.y - start.y) / 2.0 * if is_right { -1.0 } else { 1.0 },
-                y: (start.y + end.y) / 2.0
-                    + (start.x - end.x) / 2.0 * if is_right { -1.0 } else { 1.0 },
-            };
-            recursive(paths, start, mid, iterations - 1, true);
-            recursive(paths, mid, end, iterations - 1, false);
-        }
+    /// The formula animation's formulas, each clicked to type into, and a
+    /// slider for every other name they read.
+    fn render_formulas(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .absolute()
+            .top_2()
+            .right_2()
+            .w(px(360.))
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .rounded_md()
+            .occlude()
+            .bg(gpui::black().opacity(0.7))
+            .text_color(gpui::white())
+            .text_sm()
+            .children(
+                self.plot
+                    .formulas()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(ix, formula)| {
+                        let editing = self
+                            .editing
+                            .as_ref()
+                            .is_some_and(|editing| editing.formula == ix);
+                        let source = if editing {
+                            format!("{}|", formula.source)
+                        } else {
+                            formula.source.clone()
+                        };
+                        div()
+                            .id(ix)
+                            .flex()
+                            .gap_2()
+                            .px_2()
+                            .py_1()
+                            .rounded_sm()
+                            .cursor_pointer()
+                            .hover(|style| style.bg(gpui::white().opacity(0.1)))
+                            .when(editing, |this| this.bg(gpui::white().opacity(0.2)))
+                            .when(formula.expression.is_err(), |this| {
+                                this.text_color(gpui::red())
+                            })
+                            .on_click(cx.listener(move |this, _, _, cx| this.edit_formula(ix, cx)))
+                            .child(
+                                div()
+                                    .w(px(88.))
+                                    .text_color(gpui::white().opacity(0.6))
+                                    .child(formula.label),
+                            )
+                            .child(div().flex_1().child(source))
+                    }),
+            )
+            .when_some(self.plot.error(), |this, error| {
+                this.child(div().px_2().text_color(gpui::red()).child(error))
+            })
+            .children(self.plot.sliders.iter().enumerate().map(|(ix, slider)| {
+                let filled = (slider.fraction() * (SLIDER_STEPS - 1) as f64).round() as usize;
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .child(format!("{} {:.2}", slider.name, slider.value)),
+                    )
+                    .children((0..SLIDER_STEPS).map(|step| {
+                        let fraction = step as f64 / (SLIDER_STEPS - 1) as f64;
+                        div()
+                            .w(px(5.))
+                            .h(px(10.))
+                            .cursor_pointer()
+                            .bg(gpui::white().opacity(if step <= filled { 0.8 } else { 0.2 }))
+                            .on_mouse_down(
+                                MouseButton::Left,
+                                cx.listener(move |this, _: &MouseDownEvent, _, cx| {
+                                    this.set_slider(ix, fraction, cx)
+                                }),
+                            )
+                            .on_mouse_move(cx.listener(
+                                move |this, event: &MouseMoveEvent, _, cx| {
+                                    if event.pressed_button == Some(MouseButton::Left) {
+                                        this.set_slider(ix, fraction, cx);
+                                    }
+                                },
+                            ))
+                    }))
+            }))
     }
-}
 
-mod koch {
-    use super::*;
+    /// Play and repeat controls with a scrub bar along the bottom of the window.
+    fn render_transport(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let timeline = &self.timeline;
+        let status = match self.active.map(|ix| self.tracks[ix].keyframes.len()) {
+            Some(0) => "k adds a keyframe".to_string(),
+            Some(keyframes) => format!(
+                "{:.1} / {:.1} s, {keyframes} keyframes (k / shift-k)",
+                timeline.time(),
+                timeline.duration
+            ),
+            None => format!("{:.1} / {:.1} s", timeline.time(), timeline.duration),
+        };
+        let play = if timeline.is_playing() {
+            "Pause"
+        } else {
+            "Play"
+        };
 
-    pub fn snowflake(
-        start: Point<Pixels>,
-        side_length: f32,
-        iterations: u32,
-    ) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
-        let mut paths = Vec::new();
-        let height = side_length * 3f32.sqrt() / 2.0;
+        div()
+            .absolute()
+            .bottom_0()
+            .left_0()
+            .w_full()
+            .flex()
+            .flex_col()
+            .occlude()
+            .bg(gpui::black().opacity(0.7))
+            .text_color(gpui::white())
+            .text_sm()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .p_2()
+                    .child(
+                        button("play", play).on_click(cx.listener(|this, _, window, cx| {
+                            this.toggle_playback(&TogglePlayback, window, cx)
+                        })),
+                    )
+                    .child(button("repeat", timeline.repeat.name()).on_click(
+                        cx.listener(|this, _, window, cx| {
+                            this.next_repeat(&NextRepeat, window, cx)
+                        }),
+                    ))
+                    .child(div().text_color(gpui::white().opacity(0.6)).child(status)),
+            )
+            .child(
+                div()
+                    .id("scrub")
+                    .w_full()
+                    .h_2()
+                    .cursor_pointer()
+                    .bg(gpui::white().opacity(0.2))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, event: &MouseDownEvent, window, cx| {
+                            this.scrub(event.position.x, window, cx)
+                        }),
+                    )
+                    .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, window, cx| {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            this.scrub(event.position.x, window, cx);
+                        }
+                    }))
+                    .child(
+                        div()
+                            .h_full()
+                            .w(relative(timeline.progress() as f32))
+                            .bg(gpui::white().opacity(0.8)),
+                    ),
+            )
+    }
+}
 
-        let p1 = start;
-        let p2 = start + point(px(side_length), px(0.0));
-        let p3 = start + point(px(side_length / 2.0), px(height));
+impl Render for FractalViewer {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let mut all_quads = self.quads.clone();
+        all_quads.extend(self.temp_quads.clone());
+        let paths = self.paths.clone();
+        let images = self.images.clone();
 
-        side(&mut paths, p1, p2, iterations);
-        side(&mut paths, p2, p3, iterations);
-        side(&mut paths, p3, p1, iterations);
+        let key_context = if self.editing.is_some() {
+            "FractalViewer editing"
+        } else {
+            "FractalViewer"
+        };
 
-        paths
+        div()
+            .track_focus(&self.focus_handle)
+            .key_context(key_context)
+            .on_key_down(cx.listener(Self::type_key))
+            .on_action(cx.listener(Self::select_next))
+            .on_action(cx.listener(Self::select_previous))
+            .on_action(cx.listener(Self::next_palette))
+            .on_action(cx.listener(Self::next_color_mapping))
+            .on_action(cx.listener(Self::toggle_playback))
+            .on_action(cx.listener(Self::next_repeat))
+            .on_action(cx.listener(Self::add_keyframe))
+            .on_action(cx.listener(Self::clear_keyframes))
+            .on_action(cx.listener(Self::export_image))
+            .on_scroll_wheel(cx.listener(Self::zoom))
+            .on_mouse_down(MouseButton::Left, cx.listener(Self::start_drag))
+            .on_mouse_move(cx.listener(Self::drag))
+            .on_mouse_up(MouseButton::Left, cx.listener(Self::end_drag))
+            .bg(gpui::black())
+            .size_full()
+            .child(render_canvas(all_quads, paths, images, window, cx))
+            .child(self.render_picker(cx))
+            .when(self.active.is_none(), |this| {
+                this.child(self.render_formulas(cx))
+            })
+            .child(self.render_transport(cx))
     }
+}
 
-    fn side(
-        paths: &mut Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
-        start: Point<Pixels>,
-        end: Point<Pixels>,
-        iterations: u32,
-    ) {
-        if iterations == 0 {
-            paths.push(shapes::line(start, end).paint());
-        } else {
-            let delta = end - start;
-            let third = Point {
-                x: delta.x / 3.0,
-                y: delta.y / 3.0,
-            };
-
-            let p1 = start;
-            let p2 = start + third;
-            let p3 = {
-                let angle = PI / 3.0;
-                let rotated_third = Point {
-                    x: third.x * angle.cos() - third.y * angle.sin(),
-                    y: third.x * angle.sin() + third.y * angle.cos(),
-                };
-                p2 + rotated_third
-            };
-            let p4 = start
-                + Point {
-                    x: 2.0 * third.x,
-                    y: 2.0 * third.y,
-                };
-            let p5 = end;
+/// A clickable label styled like the picker's entries.
+fn button(id: impl Into<ElementId>, label: impl Into<SharedString>) -> Stateful<Div> {
+    div()
+        .id(id)
+        .px_2()
+        .rounded_sm()
+        .cursor_pointer()
+        .hover(|style| style.bg(gpui::white().opacity(0.1)))
+        .child(label.into())
+}
 
-            side(paths, p1, p2, iterations - 1);
-            side(paths, p2, p3, iterations - 1);
-            side(paths, p3, p4, iterations - 1);
-            side(paths, p4, p5, iterations - 1);
-        }
+/// A tenth of the value's leading decimal digit, so each step changes a
+/// parameter by a similar proportion whatever its size.
+fn parameter_step(value: f64) -> f64 {
+    if value == 0.0 {
+        return 0.01;
     }
+    10f64.powf(value.abs().log10().floor() - 1.0).max(0.001)
 }
 
-mod sierpinski {
-    use gpui::Point;
+fn render_canvas(
+    quads: Vec<gpui::PaintQuad>,
+    paths: Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
+    images: Vec<(Bounds<Pixels>, Arc<RenderImage>)>,
+    _window: &mut Window,
+    _cx: &mut App,
+) -> impl IntoElement {
+    canvas(
+        |_, _, _| {},
+        move |_, _, window, _| {
+            for (bounds, image) in images {
+                window
+                    .paint_image(bounds, Corners::default(), image, 0, false)
+                    .ok();
+            }
+            for quad in quads.iter() {
+                window.paint_quad(quad.clone());
+            }
+            for (path, color) in paths.iter() {
+                window.paint_path(path.clone(), *color);
+            }
+        },
+    )
+    .size_full()
+}
 
-    use super::*;
+fn fractals() -> Vec<Box<dyn fractal::Fractal>> {
+    let mut fractals: Vec<Box<dyn fractal::Fractal>> = vec![
+        Box::new(JuliaSet::default()),
+        Box::new(Mandelbrot::default()),
+        Box::new(Mandelbrot::burning_ship()),
+        Box::new(JuliaSet::burning_ship()),
+        Box::new(Mandelbrot::tricorn()),
+        Box::new(JuliaSet::tricorn()),
+        Box::new(Mandelbrot::multibrot(3.0)),
+        Box::new(JuliaSet::multibrot(3.0)),
+        Box::new(DragonCurve::default()),
+        Box::new(KochSnowflake::default()),
+        Box::new(SierpinskiTriangle::default()),
+        Box::new(PythagorasTree::default()),
+        Box::new(CircularCarpet::default()),
+    ];
+    fractals.extend(
+        newton::presets()
+            .into_iter()
+            .map(|preset| Box::new(preset) as Box<dyn fractal::Fractal>),
+    );
+    fractals.extend(
+        flame::presets()
+            .into_iter()
+            .map(|preset| Box::new(preset) as Box<dyn fractal::Fractal>),
+    );
+    fractals.extend(
+        ifs::presets()
+            .into_iter()
+            .map(|preset| Box::new(preset) as Box<dyn fractal::Fractal>),
+    );
+    fractals.extend(
+        lsystem::presets()
+            .into_iter()
+            .map(|preset| Box::new(preset) as Box<dyn fractal::Fractal>),
+    );
+    fractals
+}
 
-    pub fn triangle(
-        start: Point<Pixels>,
-        side_length: f32,
-        iterations: u32,
-    ) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
-        let mut paths = Vec::new();
-        recursive(&mut paths, start, side_length, iterations);
-        paths
-    }
+const EXPORT_USAGE: &str = "usage: gpui-fractals export <fractal> <output.png|output.svg> \
+    [--size WIDTHxHEIGHT] [--depth N] [--palette NAME]";
 
-    fn recursive(
-        paths: &mut Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
-        start: Point<Pixels>,
-        side_length: f32,
-        iterations: u32,
-    ) {
-        if iterations == 0 {
-            let height = side_length * 3f32.sqrt() / 2.0;
-            let p1 = start;
-            let p2 = start + point(px(side_length), px(0.0));
-            let p3 = start + point(px(side_length / 2.0), px(height));
-            paths.push(shapes::triangle(p1, p2, p3).paint());
-        } else {
-            let new_side = side_length / 2.0;
-            recursive(paths, start, new_side, iterations - 1);
-            recursive(
-                paths,
-                start + point(px(new_side), px(0.0)),
-                new_side,
-                iterations - 1,
-            );
-            recursive(
-                paths,
-                start + point(px(new_side / 2.0), px(new_side * 3f32.sqrt() / 2.0)),
-                new_side,
-                iterations - 1,
-            );
-        }
-    }
+const VIDEO_USAGE: &str = "usage: gpui-fractals video <fractal> <keyframes.txt> <output-dir> \
+    [--size WIDTHxHEIGHT] [--fps N] [--palette NAME]";
+
+/// Picker names are matched loosely on the command line: "Julia set" is `julia-set`.
+fn slug(name: &str) -> String {
+    name.to_lowercase().replace(' ', "-")
 }
 
-mod pythagoras {
-    use gpui::Point;
+/// The fractal the picker lists as `name`, with its default settings.
+fn find_fractal(name: &str) -> Result<Box<dyn fractal::Fractal>, String> {
+    fractals()
+        .into_iter()
+        .find(|fractal| slug(fractal.name()) == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = fractals().iter().map(|f| slug(f.name())).collect();
+            format!(
+                "unknown fractal {name:?}, expected one of {}",
+                names.join(", ")
+            )
+        })
+}
 
-    use super::*;
+fn parse_size(value: &str) -> Result<(usize, usize), String> {
+    value
+        .split_once('x')
+        .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+        .ok_or_else(|| format!("invalid size {value:?}"))
+}
 
-    pub fn tree(
-        start: Point<Pixels>,
-        size: f32,
-        angle: f32,
-        iterations: u32,
-    ) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
-        let mut paths = Vec::new();
-        recursive(&mut paths, start, size, angle, iterations);
-        paths
-    }
+fn set_palette(fractal: &mut dyn fractal::Fractal, value: &str) -> Result<(), String> {
+    let palette = Palette::builtin()
+        .into_iter()
+        .find(|palette| palette.name.eq_ignore_ascii_case(value))
+        .ok_or_else(|| format!("unknown palette {value:?}"))?;
+    let name = fractal.name();
+    *fractal
+        .palette_mut()
+        .ok_or_else(|| format!("{name} has no palette"))? = palette;
+    Ok(())
+}
 
-    fn recursive(
-        paths: &mut Vec<(gpui::Path<Pixels>, gpui::Hsla)>,
-        start: Point<Pixels>,
-        size: f32,
-        angle: f32,
-        iterations: u32,
-    ) {
-        if iterations == 0 {
-            return;
+/// Renders a fractal straight to a PNG, or an SVG for shape fractals, without
+/// opening a window. `--size` and `--palette` only apply to PNGs. Press `e` in
+/// the viewer to export a fractal as it is shown there instead.
+fn export(args: &[String]) -> Result<(), String> {
+    let [name, output, options @ ..] = args else {
+        return Err(EXPORT_USAGE.into());
+    };
+    let mut fractal = find_fractal(name)?;
+
+    let (mut width, mut height) = (4096, 4096);
+    let mut depth = fractal.default_depth();
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| format!("{option} needs a value\n{EXPORT_USAGE}"))?;
+        match option.as_str() {
+            "--size" => (width, height) = parse_size(value)?,
+            "--depth" => {
+                depth = value
+                    .parse()
+                    .map_err(|_| format!("invalid depth {value:?}"))?;
+            }
+            "--palette" => set_palette(fractal.as_mut(), value)?,
+            _ => return Err(format!("unknown option {option:?}\n{EXPORT_USAGE}")),
         }
+    }
 
-        let end = Point {
-            x: start.x + px(size * angle.cos()),
-            y: start.y - px(size * angle.sin()),
-        };
+    let result = if output.to_lowercase().ends_with(".svg") {
+        export::svg(fractal.as_ref(), depth, output)
+    } else {
+        export::png(fractal.as_mut(), depth, width, height, output)
+    };
+    result.map_err(|error| error.to_string())
+}
 
-        paths.push(shapes::line(start, end).paint());
+/// Renders a keyframed camera path over a fractal as numbered PNG frames, for
+/// stitching into a zoom video with a tool like ffmpeg. The keyframe file's
+/// format is described at [`Track::parse`].
+fn video(args: &[String]) -> Result<(), String> {
+    let [name, keyframes, output, options @ ..] = args else {
+        return Err(VIDEO_USAGE.into());
+    };
+    let mut fractal = find_fractal(name)?;
+
+    let (mut width, mut height) = (1920, 1080);
+    let mut fps = 30.0;
+    let mut options = options.iter();
+    while let Some(option) = options.next() {
+        let value = options
+            .next()
+            .ok_or_else(|| format!("{option} needs a value\n{VIDEO_USAGE}"))?;
+        match option.as_str() {
+            "--size" => (width, height) = parse_size(value)?,
+            "--fps" => {
+                fps = value
+                    .parse()
+                    .ok()
+                    .filter(|fps: &f64| *fps > 0.0)
+                    .ok_or_else(|| format!("invalid fps {value:?}"))?;
+            }
+            "--palette" => set_palette(fractal.as_mut(), value)?,
+            _ => return Err(format!("unknown option {option:?}\n{VIDEO_USAGE}")),
+        }
+    }
 
-        let new_size = size / 2f32.sqrt();
-        let new_angle1 = angle + PI / 4.0;
-        let new_angle2 = angle - PI / 4.0;
+    let text = std::fs::read_to_string(keyframes)
+        .map_err(|error| format!("failed to read {keyframes}: {error}"))?;
+    let viewport = fractal
+        .viewport_mut()
+        .map(|viewport| *viewport)
+        .ok_or_else(|| {
+            let names: Vec<_> = fractals()
+                .into_iter()
+                .filter_map(|mut candidate| {
+                    let has_camera = candidate.viewport_mut().is_some();
+                    has_camera.then(|| slug(candidate.name()))
+                })
+                .collect();
+            format!(
+                "{} has no camera to keyframe; keyframed camera paths need a \
+                 complex-plane fractal, one of {}",
+                fractal.name(),
+                names.join(", ")
+            )
+        })?;
+    let track = Track::parse(&text, &viewport, &fractal.parameters())
+        .map_err(|error| format!("{keyframes}: {error}"))?;
+
+    export::frames(
+        fractal.as_mut(),
+        &track,
+        fps,
+        width,
+        height,
+        output,
+        |done, total| eprint!("\rframe {done}/{total}"),
+    )
+    .map_err(|error| error.to_string())?;
+    eprintln!();
+    Ok(())
+}
 
-        recursive(paths, end, new_size, new_angle1, iterations - 1);
-        recursive(paths, end, new_size, new_angle2, iterations - 1);
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("export") => Some(export(&args[1..])),
+        Some("video") => Some(video(&args[1..])),
+        _ => None,
+    };
+    if let Some(result) = result {
+        if let Err(error) = result {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+        return;
     }
+
+    Application::new().run(|cx| {
+        // Typing into a formula needs every key for itself.
+        const VIEWER_KEYS: Option<&str> = Some("FractalViewer && !editing");
+        cx.bind_keys([
+            KeyBinding::new("down", SelectNext, VIEWER_KEYS),
+            KeyBinding::new("up", SelectPrevious, VIEWER_KEYS),
+            KeyBinding::new("p", NextPalette, VIEWER_KEYS),
+            KeyBinding::new("m", NextColorMapping, VIEWER_KEYS),
+            KeyBinding::new("space", TogglePlayback, VIEWER_KEYS),
+            KeyBinding::new("r", NextRepeat, VIEWER_KEYS),
+            KeyBinding::new("k", AddKeyframe, VIEWER_KEYS),
+            KeyBinding::new("shift-k", ClearKeyframes, VIEWER_KEYS),
+            KeyBinding::new("e", ExportImage, VIEWER_KEYS),
+        ]);
+        cx.open_window(
+            WindowOptions {
+                titlebar: Some(TitlebarOptions {
+                    title: Some("gpui".into()),
+                    ..Default::default()
+                }),
+                window_bounds: Some(gpui::WindowBounds::Windowed(Bounds::new(
+                    point(px(0.), px(0.)),
+                    Size::new(px(768.), px(768.)),
+                ))),
+                focus: true,
+                ..Default::default()
+            },
+            |window, cx| cx.new(|cx| FractalViewer::new(fractals(), window, cx)),
+        )
+        .unwrap();
+        cx.activate(true);
+    });
 }
@@ -0,0 +1,191 @@
+//! The Mandelbrot set: every `c` for which `z * z + c` stays bounded from
+//! `z = 0`. Each of its points is the parameter of a connected Julia set.
+
+use crate::{
+    escape_time::{self, Formula, Precision, RendererBuilder, Sampler, TileRenderer},
+    fractal,
+    julia_set::JuliaSet,
+    palette::{Coloring, Mapping, Palette},
+    perturbation::{Form, ReferenceOrbit},
+    raster::Raster,
+    viewport::Viewport,
+};
+use gpui::{Pixels, Point};
+use num_complex::Complex;
+use std::sync::Arc;
+
+/// Parameters for [`generate`]. As with [`JuliaSet`], the depth is the
+/// iteration limit.
+pub struct Mandelbrot {
+    pub viewport: Viewport,
+    pub formula: Formula,
+    pub coloring: Coloring,
+}
+
+impl Default for Mandelbrot {
+    fn default() -> Self {
+        Mandelbrot {
+            viewport: Viewport {
+                center: Complex::new(-0.5, 0.0),
+                scale: 1.5,
+                ..Viewport::default()
+            },
+            formula: Formula::Quadratic,
+            coloring: Coloring::new(Palette::ocean(), Mapping::Equalized),
+        }
+    }
+}
+
+impl Mandelbrot {
+    /// Drawn with the imaginary axis growing downwards, as usual, so the ship
+    /// sails upright.
+    pub fn burning_ship() -> Self {
+        Mandelbrot {
+            viewport: Viewport {
+                center: Complex::new(-0.4, -0.6),
+                scale: 1.8,
+                ..Viewport::default()
+            },
+            formula: Formula::BurningShip,
+            coloring: Coloring::new(Palette::fire(), Mapping::Equalized),
+        }
+    }
+
+    pub fn tricorn() -> Self {
+        Mandelbrot {
+            viewport: Viewport {
+                center: Complex::new(-0.3, 0.0),
+                scale: 1.6,
+                ..Viewport::default()
+            },
+            formula: Formula::Tricorn,
+            ..Default::default()
+        }
+    }
+
+    /// `z^exponent + c`, which has `exponent - 1`-fold symmetry for whole
+    /// exponents.
+    pub fn multibrot(exponent: f32) -> Self {
+        Mandelbrot {
+            viewport: Viewport {
+                scale: 1.5,
+                ..Viewport::default()
+            },
+            formula: Formula::multibrot(exponent),
+            coloring: Coloring::new(Palette::electric(), Mapping::Equalized),
+        }
+    }
+}
+
+impl fractal::Fractal for Mandelbrot {
+    fn name(&self) -> &'static str {
+        match self.formula {
+            Formula::Quadratic => "Mandelbrot set",
+            Formula::BurningShip => "Burning Ship",
+            Formula::Tricorn => "Tricorn",
+            Formula::Multibrot { .. } => "Multibrot",
+        }
+    }
+
+    /// High enough for deep zooms, which need far more iterations than the
+    /// default to resolve.
+    fn max_depth(&self) -> u32 {
+        100_000
+    }
+
+    fn default_depth(&self) -> u32 {
+        100
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        generate(&self.viewport, self.formula, depth, &self.coloring).into()
+    }
+
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        Some(&mut self.viewport)
+    }
+
+    fn tile_renderer(&self, depth: u32) -> Option<RendererBuilder> {
+        let max_iterations = depth.min(self.max_depth());
+        let (viewport, formula) = (self.viewport, self.formula);
+        let coloring = self.coloring.clone();
+        Some(Box::new(move || {
+            Arc::new(sampler(&viewport, formula, max_iterations, &coloring))
+                as Arc<dyn TileRenderer>
+        }))
+    }
+
+    fn coloring(&self) -> Option<&Coloring> {
+        Some(&self.coloring)
+    }
+
+    fn coloring_mut(&mut self) -> Option<&mut Coloring> {
+        Some(&mut self.coloring)
+    }
+
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        self.formula.parameters()
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        self.formula.set_parameter(name, value)
+    }
+
+    /// Opens the Julia set whose `c` is the clicked point, found from the
+    /// precise center so deep zooms pick the point under the cursor.
+    fn pick(&self, position: Point<Pixels>) -> Option<Box<dyn fractal::Fractal>> {
+        let (re, im) = self.viewport.precise_center();
+        let offset = self
+            .viewport
+            .offset(position.x.0 as f64, position.y.0 as f64);
+        Some(Box::new(JuliaSet {
+            viewport: Viewport::new(self.viewport.width, self.viewport.height),
+            formula: self.formula,
+            c: Complex::new((re + offset.re).to_f64(), (im + offset.im).to_f64()),
+            ..Default::default()
+        }))
+    }
+}
+
+/// Samples the part of the plane `viewport` shows, iterating `formula` from
+/// zero with each point as `c`, and colors every pixel by how quickly its
+/// point escapes.
+pub fn generate(
+    viewport: &Viewport,
+    formula: Formula,
+    max_iterations: u32,
+    coloring: &Coloring,
+) -> Raster {
+    sampler(viewport, formula, max_iterations, coloring).render()
+}
+
+/// The tile renderer behind [`generate`], in whichever [`Precision`] the
+/// zoom calls for.
+pub fn sampler(
+    viewport: &Viewport,
+    formula: Formula,
+    max_iterations: u32,
+    coloring: &Coloring,
+) -> Sampler<impl Fn(Complex<f64>) -> Option<f32> + Send + Sync> {
+    let center = viewport.center;
+    let precision = Precision::for_view(viewport, formula);
+    let reference = (precision == Precision::Perturbation)
+        .then(|| ReferenceOrbit::new(Form::Mandelbrot, viewport, max_iterations));
+    Sampler::new(
+        *viewport,
+        max_iterations,
+        coloring.clone(),
+        move |offset: Complex<f64>| {
+            if let Some(reference) = &reference {
+                return reference.escape(offset, max_iterations);
+            }
+            let c = center + offset;
+            if precision == Precision::Single {
+                let c = Complex::<f32>::new(c.re as f32, c.im as f32);
+                escape_time::escape(formula, Complex::<f32>::new(0.0, 0.0), c, max_iterations)
+            } else {
+                escape_time::escape(formula, Complex::<f64>::new(0.0, 0.0), c, max_iterations)
+            }
+        },
+    )
+}
@@ -0,0 +1,312 @@
+//! Newton's method basins: every point of the plane is run through Newton's
+//! method for a polynomial and colored by the root it lands on, darker the
+//! longer it took to get there.
+
+use crate::{
+    escape_time::{RendererBuilder, Tile, TileRenderer},
+    fractal,
+    palette::Palette,
+    raster::Raster,
+    viewport::Viewport,
+};
+use num_complex::Complex;
+use std::sync::Arc;
+
+/// How close a point has to come to a root to count as converged.
+const TOLERANCE: f64 = 1e-6;
+
+/// Rounds of Durand–Kerner when finding the roots of a polynomial given by
+/// its coefficients.
+const ROOT_ITERATIONS: usize = 500;
+
+/// A polynomial with complex coefficients, lowest degree first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polynomial {
+    pub coefficients: Vec<Complex<f64>>,
+}
+
+impl Polynomial {
+    /// `coefficients[k]` multiplies `z^k`. Trailing zeros are dropped.
+    pub fn new(coefficients: impl IntoIterator<Item = Complex<f64>>) -> Self {
+        let mut coefficients: Vec<_> = coefficients.into_iter().collect();
+        while coefficients
+            .last()
+            .is_some_and(|c| *c == Complex::new(0.0, 0.0))
+        {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    /// The monic polynomial `(z - roots[0]) (z - roots[1]) ...`.
+    pub fn from_roots(roots: &[Complex<f64>]) -> Self {
+        let mut coefficients = vec![Complex::new(1.0, 0.0)];
+        for root in roots {
+            // Multiply by (z - root).
+            let mut next = vec![Complex::new(0.0, 0.0); coefficients.len() + 1];
+            for (k, c) in coefficients.iter().enumerate() {
+                next[k + 1] += c;
+                next[k] -= c * root;
+            }
+            coefficients = next;
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn degree(&self) -> usize {
+        self.coefficients.len().saturating_sub(1)
+    }
+
+    /// The value and derivative at `z`, both by Horner's rule.
+    pub fn eval(&self, z: Complex<f64>) -> (Complex<f64>, Complex<f64>) {
+        let mut value = Complex::new(0.0, 0.0);
+        let mut derivative = Complex::new(0.0, 0.0);
+        for c in self.coefficients.iter().rev() {
+            derivative = derivative * z + value;
+            value = value * z + c;
+        }
+        (value, derivative)
+    }
+
+    /// Every root, repeated roots included, found with the Durand–Kerner
+    /// method.
+    pub fn roots(&self) -> Vec<Complex<f64>> {
+        let degree = self.degree();
+        if degree == 0 {
+            return Vec::new();
+        }
+        let lead = self.coefficients[degree];
+        let monic = Polynomial::new(self.coefficients.iter().map(|c| c / lead));
+        let seed = Complex::new(0.4, 0.9);
+        let mut roots: Vec<_> = (0..degree).map(|k| seed.powu(k as u32)).collect();
+
+        for _ in 0..ROOT_ITERATIONS {
+            let mut moved = 0.0f64;
+            for i in 0..degree {
+                let others = (0..degree)
+                    .filter(|&j| j != i)
+                    .map(|j| roots[i] - roots[j])
+                    .fold(Complex::new(1.0, 0.0), |product, difference| {
+                        product * difference
+                    });
+                if others == Complex::new(0.0, 0.0) {
+                    continue;
+                }
+                let step = monic.eval(roots[i]).0 / others;
+                roots[i] -= step;
+                moved = moved.max(step.norm());
+            }
+            if moved < TOLERANCE * TOLERANCE {
+                break;
+            }
+        }
+        roots
+    }
+}
+
+/// Parameters for [`generate`]. The depth is the iteration limit.
+#[derive(Clone, Debug)]
+pub struct Newton {
+    pub name: &'static str,
+    pub viewport: Viewport,
+    pub polynomial: Polynomial,
+    /// The roots basins are colored by, in palette order.
+    pub roots: Vec<Complex<f64>>,
+    /// Root `k` of `n` takes the palette color at `(k + 0.5) / n`.
+    pub palette: Palette,
+}
+
+impl Newton {
+    /// Finds the roots of `polynomial` numerically.
+    pub fn new(name: &'static str, polynomial: Polynomial) -> Self {
+        let roots = polynomial.roots();
+        Newton {
+            name,
+            viewport: Viewport::default(),
+            polynomial,
+            roots,
+            palette: Palette::rainbow(),
+        }
+    }
+
+    /// The polynomial with exactly these roots, colored in this order.
+    pub fn from_roots(name: &'static str, roots: Vec<Complex<f64>>) -> Self {
+        Newton {
+            polynomial: Polynomial::from_roots(&roots),
+            roots,
+            ..Newton::new(name, Polynomial::new([]))
+        }
+    }
+
+    /// `z^3 - 1`, whose three basins meet only at their boundaries.
+    pub fn cubic() -> Newton {
+        let (zero, one) = (Complex::new(0.0, 0.0), Complex::new(1.0, 0.0));
+        Newton::new("Newton cubic", Polynomial::new([-one, zero, zero, one]))
+    }
+
+    /// The five fifth roots of unity.
+    pub fn quintic() -> Newton {
+        let roots = (0..5)
+            .map(|k| Complex::from_polar(1.0, k as f64 * std::f64::consts::TAU / 5.0))
+            .collect();
+        Newton::from_roots("Newton quintic", roots)
+    }
+
+    /// `z^3 - 2z + 2`, where Newton's method falls into a cycle between 0 and
+    /// 1 for a whole region of starting points, left uncolored.
+    pub fn cycle() -> Newton {
+        let re = |re: f64| Complex::new(re, 0.0);
+        Newton::new(
+            "Newton cycle",
+            Polynomial::new([re(2.0), re(-2.0), re(0.0), re(1.0)]),
+        )
+    }
+}
+
+impl fractal::Fractal for Newton {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn max_depth(&self) -> u32 {
+        200
+    }
+
+    fn default_depth(&self) -> u32 {
+        40
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        generate(self, depth).into()
+    }
+
+    fn viewport_mut(&mut self) -> Option<&mut Viewport> {
+        Some(&mut self.viewport)
+    }
+
+    fn tile_renderer(&self, depth: u32) -> Option<RendererBuilder> {
+        let renderer = renderer(self, depth.min(self.max_depth()));
+        Some(Box::new(move || {
+            Arc::new(renderer) as Arc<dyn TileRenderer>
+        }))
+    }
+
+    fn palette(&self) -> Option<&Palette> {
+        Some(&self.palette)
+    }
+
+    fn palette_mut(&mut self) -> Option<&mut Palette> {
+        Some(&mut self.palette)
+    }
+}
+
+/// Every preset, in the order a picker should list them.
+pub fn presets() -> Vec<Newton> {
+    vec![Newton::cubic(), Newton::quintic(), Newton::cycle()]
+}
+
+/// Runs Newton's method from `z` and returns the index of the root it
+/// converges to, with a smooth count of the steps that took. Points that
+/// don't settle on a root within `max_iterations` return `None`.
+pub fn converge(
+    polynomial: &Polynomial,
+    roots: &[Complex<f64>],
+    mut z: Complex<f64>,
+    max_iterations: u32,
+) -> Option<(usize, f32)> {
+    let mut previous = f64::INFINITY;
+    for i in 0..max_iterations {
+        let (ix, distance) = roots
+            .iter()
+            .map(|root| (z - root).norm())
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(&b.1))?;
+        if distance < TOLERANCE {
+            let count = i as f32 + smooth_fraction(previous, distance) - 1.0;
+            return Some((ix, count.max(0.0)));
+        }
+        previous = distance;
+
+        let (value, derivative) = polynomial.eval(z);
+        if derivative.norm_sqr() == 0.0 {
+            return None;
+        }
+        z -= value / derivative;
+    }
+    None
+}
+
+/// Where between the last two steps the distance to the root crossed
+/// [`TOLERANCE`], measured on a log-log scale since convergence is quadratic.
+fn smooth_fraction(previous: f64, distance: f64) -> f32 {
+    let (previous, distance) = (previous.ln(), distance.max(f64::MIN_POSITIVE).ln());
+    if previous >= 0.0 || distance >= previous {
+        return 0.0;
+    }
+    let fraction = (TOLERANCE.ln() / previous).ln() / (distance / previous).ln();
+    (fraction as f32).clamp(0.0, 1.0)
+}
+
+/// Colors every canvas pixel of `newton`'s viewport by the basin its point
+/// falls in.
+pub fn generate(newton: &Newton, max_iterations: u32) -> Raster {
+    renderer(newton, max_iterations).render()
+}
+
+/// The tile renderer behind [`generate`].
+pub fn renderer(newton: &Newton, max_iterations: u32) -> BasinRenderer {
+    BasinRenderer {
+        viewport: newton.viewport,
+        polynomial: newton.polynomial.clone(),
+        roots: newton.roots.clone(),
+        max_iterations,
+        palette: newton.palette.clone(),
+    }
+}
+
+/// Colors each pixel by its root, shaded by how many steps it took.
+pub struct BasinRenderer {
+    viewport: Viewport,
+    polynomial: Polynomial,
+    roots: Vec<Complex<f64>>,
+    max_iterations: u32,
+    palette: Palette,
+}
+
+impl TileRenderer for BasinRenderer {
+    fn size(&self) -> (usize, usize) {
+        (self.viewport.width, self.viewport.height)
+    }
+
+    fn render_tile(&self, tile: Tile) -> Raster {
+        let mut raster = Raster::new(tile.width, tile.height);
+        let roots = self.roots.len().max(1) as f32;
+
+        for y in 0..tile.height {
+            for x in 0..tile.width {
+                let point = self
+                    .viewport
+                    .to_complex((tile.x + x) as f64, (tile.y + y) as f64);
+                let Some((ix, count)) =
+                    converge(&self.polynomial, &self.roots, point, self.max_iterations)
+                else {
+                    // Points that never settle stay transparent.
+                    continue;
+                };
+                let mut color = self.palette.sample((ix as f32 + 0.5) / roots);
+                // Most points converge in a handful of steps; the square root spreads
+                // those few steps over more of the shading.
+                let shade = 1.0
+                    - (count / self.max_iterations.max(1) as f32)
+                        .clamp(0.0, 1.0)
+                        .sqrt();
+                color.r *= shade;
+                color.g *= shade;
+                color.b *= shade;
+                raster.set_color(x, y, color);
+            }
+        }
+
+        raster
+    }
+}
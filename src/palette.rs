@@ -0,0 +1,262 @@
+//! Gradient palettes and the ways escape counts are mapped onto them.
+
+use gpui::{hsla, rgb, transparent_black, Rgba, SharedString};
+
+/// A named gradient.
+#[derive(Clone, Debug)]
+pub struct Palette {
+    pub name: SharedString,
+    /// `(position, color)` stops, sorted by position within `0..=1`.
+    stops: Vec<(f32, Rgba)>,
+}
+
+impl Palette {
+    /// A gradient through `stops`. Positions are clamped to `0..=1` and may be
+    /// given in any order. Panics if `stops` is empty.
+    pub fn new(
+        name: impl Into<SharedString>,
+        stops: impl IntoIterator<Item = (f32, Rgba)>,
+    ) -> Self {
+        let mut stops: Vec<_> = stops
+            .into_iter()
+            .map(|(position, color)| (position.clamp(0.0, 1.0), color))
+            .collect();
+        assert!(!stops.is_empty(), "a palette needs at least one stop");
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Palette {
+            name: name.into(),
+            stops,
+        }
+    }
+
+    /// A gradient with `colors` spread evenly from 0 to 1.
+    pub fn evenly_spaced(
+        name: impl Into<SharedString>,
+        colors: impl IntoIterator<Item = Rgba>,
+    ) -> Self {
+        let colors: Vec<_> = colors.into_iter().collect();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+        Palette::new(
+            name,
+            colors
+                .into_iter()
+                .enumerate()
+                .map(|(ix, color)| (ix as f32 / last, color)),
+        )
+    }
+
+    /// The color at `t`, blending linearly between the neighbouring stops.
+    /// `t` is clamped to `0..=1`.
+    pub fn sample(&self, t: f32) -> Rgba {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let ix = self.stops.partition_point(|(position, _)| *position <= t);
+        if ix == 0 {
+            return self.stops[0].1;
+        }
+        if ix == self.stops.len() {
+            return self.stops[ix - 1].1;
+        }
+
+        let (start, from) = self.stops[ix - 1];
+        let (end, to) = self.stops[ix];
+        let f = (t - start) / (end - start);
+        Rgba {
+            r: from.r + (to.r - from.r) * f,
+            g: from.g + (to.g - from.g) * f,
+            b: from.b + (to.b - from.b) * f,
+            a: from.a + (to.a - from.a) * f,
+        }
+    }
+
+    /// Every built-in palette, in the order a picker should cycle through them.
+    pub fn builtin() -> Vec<Palette> {
+        vec![
+            Palette::electric(),
+            Palette::ocean(),
+            Palette::fire(),
+            Palette::rainbow(),
+            Palette::grayscale(),
+        ]
+    }
+
+    /// The built-in palette after this one, by name, wrapping around.
+    pub fn next_builtin(&self) -> Palette {
+        let builtin = Palette::builtin();
+        let ix = builtin
+            .iter()
+            .position(|palette| palette.name == self.name)
+            .map_or(0, |ix| (ix + 1) % builtin.len());
+        builtin[ix].clone()
+    }
+
+    /// Deep blue through white to orange and back, so it also cycles cleanly.
+    pub fn electric() -> Palette {
+        Palette::evenly_spaced(
+            "Electric",
+            [
+                rgb(0x000764),
+                rgb(0x206bcb),
+                rgb(0xedffff),
+                rgb(0xffaa00),
+                rgb(0x000200),
+                rgb(0x000764),
+            ],
+        )
+    }
+
+    pub fn ocean() -> Palette {
+        Palette::evenly_spaced(
+            "Ocean",
+            [
+                rgb(0x020024),
+                rgb(0x090979),
+                rgb(0x00a3d4),
+                rgb(0x7ff0e6),
+                rgb(0xffffff),
+            ],
+        )
+    }
+
+    pub fn fire() -> Palette {
+        Palette::evenly_spaced(
+            "Fire",
+            [
+                rgb(0x000000),
+                rgb(0x7a0000),
+                rgb(0xff3d00),
+                rgb(0xffc400),
+                rgb(0xffffe0),
+            ],
+        )
+    }
+
+    pub fn rainbow() -> Palette {
+        Palette::evenly_spaced(
+            "Rainbow",
+            (0..=6).map(|step| hsla(step as f32 / 6.0, 1.0, 0.5, 1.0).into()),
+        )
+    }
+
+    pub fn grayscale() -> Palette {
+        Palette::evenly_spaced("Grayscale", [rgb(0x000000), rgb(0xffffff)])
+    }
+}
+
+/// How an escape count is turned into a position on the palette.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mapping {
+    /// The palette spans `0..max_iterations` once.
+    Linear,
+    /// The palette repeats every `period` iterations.
+    Cyclic { period: f32 },
+    /// Histogram equalization: each color covers about the same number of pixels.
+    Equalized,
+}
+
+impl Mapping {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mapping::Linear => "Linear",
+            Mapping::Cyclic { .. } => "Cyclic",
+            Mapping::Equalized => "Equalized",
+        }
+    }
+
+    /// The next mapping, wrapping around, for cycling through them in a UI.
+    pub fn next(&self) -> Mapping {
+        match self {
+            Mapping::Linear => Mapping::Cyclic { period: 32.0 },
+            Mapping::Cyclic { .. } => Mapping::Equalized,
+            Mapping::Equalized => Mapping::Linear,
+        }
+    }
+}
+
+/// Everything needed to color an escape-time fractal.
+#[derive(Clone, Debug)]
+pub struct Coloring {
+    pub palette: Palette,
+    pub mapping: Mapping,
+    /// The color of points that never escape.
+    pub interior: Rgba,
+}
+
+impl Coloring {
+    /// Colors with `palette`, leaving the interior transparent.
+    pub fn new(palette: Palette, mapping: Mapping) -> Self {
+        Coloring {
+            palette,
+            mapping,
+            interior: transparent_black().into(),
+        }
+    }
+
+    /// The color for a smooth escape count, or the interior color for `None`.
+    /// `histogram` is only consulted by [`Mapping::Equalized`], which falls back
+    /// to a linear mapping without one.
+    pub fn color(
+        &self,
+        count: Option<f32>,
+        max_iterations: u32,
+        histogram: Option<&Histogram>,
+    ) -> Rgba {
+        let Some(count) = count else {
+            return self.interior;
+        };
+        let linear = count / max_iterations.max(1) as f32;
+        let t = match self.mapping {
+            Mapping::Linear => linear,
+            Mapping::Cyclic { period } => (count / period.max(f32::EPSILON)).rem_euclid(1.0),
+            Mapping::Equalized => histogram.map_or(linear, |histogram| histogram.rank(count)),
+        };
+        self.palette.sample(t)
+    }
+}
+
+/// The cumulative distribution of escape counts across an image.
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// `cdf[i]` is the fraction of counts below `i + 1`.
+    cdf: Vec<f32>,
+}
+
+impl Histogram {
+    /// Buckets `counts` by whole iteration, from 0 to `max_iterations`.
+    pub fn new(counts: impl IntoIterator<Item = f32>, max_iterations: u32) -> Self {
+        let mut buckets = vec![0u32; max_iterations as usize + 1];
+        let mut total = 0;
+        for count in counts {
+            let bucket = (count.max(0.0) as usize).min(max_iterations as usize);
+            buckets[bucket] += 1;
+            total += 1;
+        }
+
+        let mut running = 0;
+        let cdf = buckets
+            .into_iter()
+            .enumerate()
+            .map(|(ix, bucket)| {
+                running += bucket;
+                if total == 0 {
+                    (ix + 1) as f32 / (max_iterations + 1) as f32
+                } else {
+                    running as f32 / total as f32
+                }
+            })
+            .collect();
+        Histogram { cdf }
+    }
+
+    /// Where `count` ranks among the counts, from 0 to 1, interpolated within
+    /// its bucket so smooth counts stay smooth.
+    pub fn rank(&self, count: f32) -> f32 {
+        let count = count.max(0.0);
+        let bucket = (count as usize).min(self.cdf.len() - 1);
+        let below = if bucket == 0 {
+            0.0
+        } else {
+            self.cdf[bucket - 1]
+        };
+        below + (self.cdf[bucket] - below) * count.fract()
+    }
+}
@@ -0,0 +1,248 @@
+//! Perturbation theory for deep zooms into the quadratic Julia and Mandelbrot
+//! sets. One reference orbit is iterated in double-double precision from the
+//! viewport's center, and every pixel only tracks its small difference from
+//! that orbit, which an f64 holds comfortably however deep the zoom.
+
+use crate::{escape_time, viewport::Viewport};
+use num_complex::Complex;
+
+/// How far, as a fraction of a pixel's spacing, the series approximation may
+/// drift from exact iteration before it stops skipping ahead. Pixels near the
+/// boundary magnify any error far faster than the spacing, hence the small
+/// fraction.
+const SERIES_TOLERANCE: f64 = 1e-9;
+
+/// Which form of `z * z + c` the canvas shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Form {
+    /// Each pixel is `c`, and `z` starts at zero.
+    Mandelbrot,
+    /// Each pixel is the starting `z`, with a fixed `c`.
+    Julia { c: Complex<f64> },
+}
+
+/// The orbit of the viewport's center, and how far every pixel can jump
+/// ahead along it with a series approximation.
+#[derive(Clone, Debug)]
+pub struct ReferenceOrbit {
+    form: Form,
+    /// The reference orbit rounded to f64, up to and including the step it
+    /// escaped on.
+    orbit: Vec<Complex<f64>>,
+    /// Iterations every pixel can skip.
+    skip: usize,
+    /// At `skip`, a pixel's difference from the reference is about
+    /// `a d + b d^2 + c d^3` for its offset `d` from the center.
+    series: [Complex<f64>; 3],
+}
+
+impl ReferenceOrbit {
+    pub fn new(form: Form, viewport: &Viewport, max_iterations: u32) -> Self {
+        let (center_re, center_im) = viewport.precise_center();
+        let (mut z_re, mut z_im, c_re, c_im) = match form {
+            Form::Mandelbrot => (0.0.into(), 0.0.into(), center_re, center_im),
+            Form::Julia { c } => (center_re, center_im, c.re.into(), c.im.into()),
+        };
+        let mut orbit = vec![Complex::new(z_re.to_f64(), z_im.to_f64())];
+        for _ in 0..max_iterations {
+            (z_re, z_im) = (z_re * z_re - z_im * z_im + c_re, z_re * z_im * 2.0 + c_im);
+            let z = Complex::new(z_re.to_f64(), z_im.to_f64());
+            orbit.push(z);
+            if z.norm_sqr() > 4.0 {
+                break;
+            }
+        }
+
+        let mut reference = ReferenceOrbit {
+            form,
+            orbit,
+            skip: 0,
+            series: [Complex::new(0.0, 0.0); 3],
+        };
+        reference.approximate(viewport, max_iterations);
+        reference
+    }
+
+    /// How many iterations every pixel skips.
+    pub fn skipped(&self) -> usize {
+        self.skip
+    }
+
+    /// Finds how many iterations the series approximation can skip across
+    /// `viewport`. Probe pixels around the edges are iterated exactly
+    /// alongside the series, which is trusted only while it agrees with every
+    /// one of them to within a fraction of a pixel's spacing.
+    fn approximate(&mut self, viewport: &Viewport, max_iterations: u32) {
+        let (width, height) = (viewport.width as f64, viewport.height as f64);
+        let probes: Vec<Complex<f64>> = [
+            (0.0, 0.0),
+            (width / 2.0, 0.0),
+            (width, 0.0),
+            (0.0, height / 2.0),
+            (width, height / 2.0),
+            (0.0, height),
+            (width / 2.0, height),
+            (width, height),
+        ]
+        .into_iter()
+        .map(|(x, y)| viewport.offset(x, y))
+        .collect();
+
+        let one = Complex::new(1.0, 0.0);
+        let zero = Complex::new(0.0, 0.0);
+        let (mut a, constant) = match self.form {
+            Form::Mandelbrot => (zero, one),
+            Form::Julia { .. } => (one, zero),
+        };
+        let (mut b, mut c) = (zero, zero);
+        let mut deltas: Vec<_> = probes.iter().map(|probe| a * probe).collect();
+        self.series = [a, b, c];
+
+        // The last entry is where the reference escapes, so stop short of it.
+        let limit = (self.orbit.len() - 1).min(max_iterations as usize);
+        for n in 0..limit.saturating_sub(1) {
+            let z = self.orbit[n] * 2.0;
+            (a, b, c) = (z * a + constant, z * b + a * a, z * c + a * b * 2.0);
+            if !a.is_finite() || !b.is_finite() || !c.is_finite() {
+                break;
+            }
+            // Neighbouring pixels end up about |a| * spacing apart.
+            let tolerance = SERIES_TOLERANCE * a.norm() * viewport.units_per_pixel();
+            let mut valid = true;
+            for (delta, probe) in deltas.iter_mut().zip(&probes) {
+                let dc = match self.form {
+                    Form::Mandelbrot => *probe,
+                    Form::Julia { .. } => zero,
+                };
+                *delta = (z + *delta) * *delta + dc;
+                let series = ((c * probe + b) * probe + a) * probe;
+                let escaped = (self.orbit[n + 1] + *delta).norm_sqr() > 4.0;
+                valid &= !escaped && (series - *delta).norm() <= tolerance;
+            }
+            if !valid {
+                break;
+            }
+            self.skip = n + 1;
+            self.series = [a, b, c];
+        }
+    }
+
+    /// The normalized iteration count for the pixel `offset` from the center,
+    /// as [`escape_time::escape`] would find it with exact arithmetic.
+    pub fn escape(&self, offset: Complex<f64>, max_iterations: u32) -> Option<f32> {
+        let [a, b, c] = self.series;
+        let mut dz = ((c * offset + b) * offset + a) * offset;
+        let dc = match self.form {
+            Form::Mandelbrot => offset,
+            Form::Julia { .. } => Complex::new(0.0, 0.0),
+        };
+
+        let mut n = self.skip;
+        for i in self.skip as u32..max_iterations {
+            let z = self.orbit[n] + dz;
+            if z.norm_sqr() > 4.0 {
+                return Some(escape_time::smooth_count(i, z.norm() as f32, 2.0));
+            }
+            // Once the pixel's orbit comes closer to zero than to the
+            // reference, or runs past the reference's end, the difference
+            // stops being small. Rebase it onto the start of the reference,
+            // where it has grown large enough for an f64 to hold the rest.
+            if z.norm_sqr() < dz.norm_sqr() || n + 1 == self.orbit.len() {
+                dz = z - self.orbit[0];
+                n = 0;
+            }
+            dz = (self.orbit[n] * 2.0 + dz) * dz + dc;
+            n += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::escape_time::Formula;
+
+    /// Few enough that direct f64 iteration is still a fair reference. Near
+    /// the boundary, a few hundred more iterations magnify even the rounding
+    /// of `c` into visibly different counts.
+    const MAX_ITERATIONS: u32 = 300;
+
+    /// A 24x24 view around `center`, shallow enough for f64.
+    fn viewport(center: Complex<f64>, scale: f64) -> Viewport {
+        Viewport {
+            center,
+            scale,
+            ..Viewport::new(24, 24)
+        }
+    }
+
+    /// The same reference iterating every pixel from the start.
+    fn without_skipping(reference: &ReferenceOrbit) -> ReferenceOrbit {
+        let zero = Complex::new(0.0, 0.0);
+        let a = match reference.form {
+            Form::Mandelbrot => zero,
+            Form::Julia { .. } => Complex::new(1.0, 0.0),
+        };
+        ReferenceOrbit {
+            skip: 0,
+            series: [a, zero, zero],
+            ..reference.clone()
+        }
+    }
+
+    /// Checks every pixel of `viewport` against `direct`, and returns how many
+    /// escaped.
+    fn compare(
+        reference: &ReferenceOrbit,
+        viewport: &Viewport,
+        direct: impl Fn(Complex<f64>) -> Option<f32>,
+    ) -> usize {
+        let mut escaped = 0;
+        for y in 0..viewport.height {
+            for x in 0..viewport.width {
+                let offset = viewport.offset(x as f64, y as f64);
+                let expected = direct(viewport.center + offset);
+                let actual = reference.escape(offset, MAX_ITERATIONS);
+                match (expected, actual) {
+                    (Some(expected), Some(actual)) => {
+                        assert!(
+                            (expected - actual).abs() < 0.05,
+                            "pixel ({x}, {y}): expected {expected}, found {actual}"
+                        );
+                        escaped += 1;
+                    }
+                    (None, None) => {}
+                    _ => panic!("pixel ({x}, {y}): expected {expected:?}, found {actual:?}"),
+                }
+            }
+        }
+        escaped
+    }
+
+    #[test]
+    fn mandelbrot_matches_direct_iteration() {
+        let viewport = viewport(Complex::new(-0.7436438870, 0.1318259042), 1e-5);
+        let reference = ReferenceOrbit::new(Form::Mandelbrot, &viewport, MAX_ITERATIONS);
+        assert!(reference.skipped() > 0);
+
+        let zero = Complex::new(0.0, 0.0);
+        let direct = |c| escape_time::escape(Formula::Quadratic, zero, c, MAX_ITERATIONS);
+        let escaped = compare(&reference, &viewport, direct);
+        assert!(escaped > 0 && escaped < viewport.width * viewport.height);
+        compare(&without_skipping(&reference), &viewport, direct);
+    }
+
+    #[test]
+    fn julia_matches_direct_iteration() {
+        let c = Complex::new(-0.8, 0.156);
+        let viewport = viewport(Complex::new(0.0092, 0.1), 1e-5);
+        let reference = ReferenceOrbit::new(Form::Julia { c }, &viewport, MAX_ITERATIONS);
+        assert!(reference.skipped() > 0);
+
+        let direct = |z| escape_time::escape(Formula::Quadratic, z, c, MAX_ITERATIONS);
+        let escaped = compare(&reference, &viewport, direct);
+        assert!(escaped > 0 && escaped < viewport.width * viewport.height);
+        compare(&without_skipping(&reference), &viewport, direct);
+    }
+}
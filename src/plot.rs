@@ -0,0 +1,326 @@
+//! Parametric plots from formulas typed in at runtime. Every formula is an
+//! [`Expression`] over `t`, which runs through a range, the animation's
+//! `epoch`, and any other names, which become sliders.
+
+use crate::expression::{self, Expression, ParseError};
+
+/// More points than any plot should need, so a tiny step can't stall a frame.
+const MAX_POINTS: usize = 10_000;
+
+/// Variables every formula can read without a slider.
+pub const BUILTIN_VARIABLES: [&str; 2] = ["t", "epoch"];
+
+/// A named value the user sets by hand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Slider {
+    pub name: String,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Slider {
+    pub fn new(name: impl Into<String>, value: f64, min: f64, max: f64) -> Self {
+        Slider {
+            name: name.into(),
+            value,
+            min,
+            max,
+        }
+    }
+
+    /// How far between `min` and `max` the value is, from zero to one.
+    pub fn fraction(&self) -> f64 {
+        if self.max == self.min {
+            return 0.0;
+        }
+        ((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    pub fn set_fraction(&mut self, fraction: f64) {
+        self.value = self.min + (self.max - self.min) * fraction.clamp(0.0, 1.0);
+    }
+}
+
+/// One formula of a plot, as typed and as parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Formula {
+    /// What a UI labels it with, like `x(t)`.
+    pub label: &'static str,
+    pub source: String,
+    pub expression: Result<Expression, ParseError>,
+}
+
+impl Formula {
+    pub fn new(label: &'static str, source: impl Into<String>) -> Self {
+        let source = source.into();
+        Formula {
+            label,
+            expression: Expression::parse(&source),
+            source,
+        }
+    }
+
+    fn eval(&self, variables: &[(&str, f64)]) -> f64 {
+        self.expression
+            .as_ref()
+            .map_or(f64::NAN, |expression| expression.eval(variables))
+    }
+}
+
+/// One plotted point, with its color as HSL components from zero to one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlotPoint {
+    pub x: f64,
+    pub y: f64,
+    pub hue: f64,
+    pub saturation: f64,
+    pub lightness: f64,
+}
+
+/// Formulas for where each point goes and what color it is, evaluated for
+/// `t` from `start` to `end` in steps of `step`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plot {
+    pub start: Formula,
+    pub end: Formula,
+    pub step: Formula,
+    pub x: Formula,
+    pub y: Formula,
+    pub hue: Formula,
+    pub saturation: Formula,
+    pub lightness: Formula,
+    /// One for every name the formulas read besides [`BUILTIN_VARIABLES`].
+    pub sliders: Vec<Slider>,
+}
+
+/// Sixteen points circling the middle of a 768px canvas, breathing and
+/// spinning with the epoch.
+impl Default for Plot {
+    fn default() -> Self {
+        // The angle of point t, and its distance from the center.
+        let angle = "t * tau / points + epoch / 128 + pi * (epoch % 2) + epoch / 320";
+        let radius = "radius * (1 + 0.5 * sin(epoch / 128) + 0.2 * sin(epoch * t))";
+        Plot {
+            start: Formula::new("t from", "0"),
+            end: Formula::new("t to", "points"),
+            step: Formula::new("t step", "1"),
+            x: Formula::new("x(t)", format!("384 + {radius} * cos({angle})")),
+            y: Formula::new("y(t)", format!("384 + {radius} * sin({angle})")),
+            hue: Formula::new("hue(t)", "(t + sin(epoch / 64)) % 1"),
+            saturation: Formula::new(
+                "saturation(t)",
+                "0.5 + 0.5 * sin(epoch / 512) * cos(t * tau)",
+            ),
+            lightness: Formula::new(
+                "lightness(t)",
+                "0.5 + 0.3 * cos(epoch / 64) * sin(t * 3 * pi)",
+            ),
+            sliders: vec![
+                Slider::new("radius", 200.0, 0.0, 400.0),
+                Slider::new("points", 16.0, 1.0, 64.0),
+            ],
+        }
+    }
+}
+
+impl Plot {
+    /// Every formula, in the order a UI should list them.
+    pub fn formulas(&self) -> [&Formula; 8] {
+        [
+            &self.x,
+            &self.y,
+            &self.hue,
+            &self.saturation,
+            &self.lightness,
+            &self.start,
+            &self.end,
+            &self.step,
+        ]
+    }
+
+    pub fn formulas_mut(&mut self) -> [&mut Formula; 8] {
+        [
+            &mut self.x,
+            &mut self.y,
+            &mut self.hue,
+            &mut self.saturation,
+            &mut self.lightness,
+            &mut self.start,
+            &mut self.end,
+            &mut self.step,
+        ]
+    }
+
+    /// Replaces the source of formula `ix`, in [`Plot::formulas`] order, and
+    /// brings the sliders in line with the names the formulas now read.
+    pub fn set_source(&mut self, ix: usize, source: impl Into<String>) {
+        if let Some(formula) = self.formulas_mut().into_iter().nth(ix) {
+            *formula = Formula::new(formula.label, source);
+        }
+        self.sync_sliders();
+    }
+
+    /// Adds a slider, from 0 to 1, for every name the formulas read that
+    /// doesn't have one yet, and drops sliders nothing reads any more.
+    /// Formulas that don't parse keep their old sliders, so a half-typed
+    /// formula doesn't lose their values.
+    fn sync_sliders(&mut self) {
+        let mut names: Vec<String> = Vec::new();
+        for formula in self.formulas() {
+            let read: Vec<&str> = match &formula.expression {
+                Ok(expression) => expression.variables(),
+                Err(_) => {
+                    let identifiers = expression::identifiers(&formula.source);
+                    self.sliders
+                        .iter()
+                        .map(|slider| slider.name.as_str())
+                        .filter(|name| identifiers.iter().any(|n| n == name))
+                        .collect()
+                }
+            };
+            for name in read {
+                if !BUILTIN_VARIABLES.contains(&name) && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        self.sliders.retain(|slider| names.contains(&slider.name));
+        for name in names {
+            if !self.sliders.iter().any(|slider| slider.name == name) {
+                self.sliders.push(Slider::new(name, 0.5, 0.0, 1.0));
+            }
+        }
+    }
+
+    /// The first formula that doesn't parse, as `label: error`.
+    pub fn error(&self) -> Option<String> {
+        self.formulas().into_iter().find_map(|formula| {
+            let error = formula.expression.as_ref().err()?;
+            Some(format!("{}: {error}", formula.label))
+        })
+    }
+
+    /// Every point of the plot at `epoch`. Empty while a formula doesn't
+    /// parse or the range doesn't make sense; points whose position comes out
+    /// as NaN or infinite are left out.
+    pub fn points(&self, epoch: f64) -> Vec<PlotPoint> {
+        if self.error().is_some() {
+            return Vec::new();
+        }
+
+        let mut variables: Vec<(&str, f64)> = self
+            .sliders
+            .iter()
+            .map(|slider| (slider.name.as_str(), slider.value))
+            .collect();
+        variables.push(("epoch", epoch));
+        variables.push(("t", f64::NAN));
+        let t = variables.len() - 1;
+
+        let start = self.start.eval(&variables);
+        let end = self.end.eval(&variables);
+        let step = self.step.eval(&variables);
+        let steps = ((end - start) / step).floor();
+        if !(step > 0.0 && steps.is_finite() && steps >= 0.0) {
+            return Vec::new();
+        }
+        let count = steps as usize + 1;
+
+        (0..count.min(MAX_POINTS))
+            .filter_map(|i| {
+                variables[t].1 = start + i as f64 * step;
+                let point = PlotPoint {
+                    x: self.x.eval(&variables),
+                    y: self.y.eval(&variables),
+                    hue: self.hue.eval(&variables),
+                    saturation: self.saturation.eval(&variables),
+                    lightness: self.lightness.eval(&variables),
+                };
+                (point.x.is_finite() && point.y.is_finite()).then_some(point)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slider_names(plot: &Plot) -> Vec<&str> {
+        plot.sliders
+            .iter()
+            .map(|slider| slider.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn default_plot_draws_every_point() {
+        let plot = Plot::default();
+        assert_eq!(plot.error(), None);
+        let points = plot.points(0.0);
+        assert_eq!(points.len(), 17);
+        let first = points[0];
+        assert!((first.x - 584.0).abs() < 1e-9);
+        assert!((first.y - 384.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn points_step_through_the_range() {
+        let mut plot = Plot::default();
+        for (ix, source) in ["t", "t * t", "0", "0", "0", "1", "2", "0.5"]
+            .into_iter()
+            .enumerate()
+        {
+            plot.set_source(ix, source);
+        }
+        let points: Vec<_> = plot.points(0.0).iter().map(|p| (p.x, p.y)).collect();
+        assert_eq!(points, vec![(1.0, 1.0), (1.5, 2.25), (2.0, 4.0)]);
+        assert!(plot.sliders.is_empty());
+    }
+
+    #[test]
+    fn points_are_empty_for_bad_ranges_and_errors() {
+        let mut plot = Plot::default();
+        plot.set_source(7, "0");
+        assert!(plot.points(0.0).is_empty());
+        plot.set_source(7, "-1");
+        assert!(plot.points(0.0).is_empty());
+
+        let mut plot = Plot::default();
+        plot.set_source(0, "384 +");
+        assert_eq!(
+            plot.error(),
+            Some("x(t): unexpected end of formula at column 6".into())
+        );
+        assert!(plot.points(0.0).is_empty());
+    }
+
+    #[test]
+    fn sliders_follow_the_names_formulas_read() {
+        let mut plot = Plot::default();
+        plot.set_source(2, "hue_shift + t");
+        assert_eq!(slider_names(&plot), vec!["radius", "points", "hue_shift"]);
+        assert_eq!(plot.sliders[2], Slider::new("hue_shift", 0.5, 0.0, 1.0));
+
+        plot.set_source(2, "t");
+        assert_eq!(slider_names(&plot), vec!["radius", "points"]);
+    }
+
+    #[test]
+    fn unparsed_formulas_keep_sliders_they_name() {
+        let mut plot = Plot::default();
+        plot.sliders[0].value = 123.0;
+        // Half-typed, but still reading `radius`, so its value survives.
+        plot.set_source(0, "384 + radius * cos(");
+        plot.set_source(1, "384 + radius *");
+        assert_eq!(slider_names(&plot), vec!["radius", "points"]);
+        assert_eq!(plot.sliders[0].value, 123.0);
+
+        // `radiusx` only contains the name; it doesn't read it.
+        plot.set_source(0, "radiusx +");
+        plot.set_source(1, "384");
+        assert_eq!(slider_names(&plot), vec!["points"]);
+    }
+}
@@ -0,0 +1,99 @@
+//! A line-drawn Pythagoras tree.
+
+use crate::{
+    fractal,
+    shapes::{self, Shape},
+};
+use gpui::{point, px, Pixels, Point};
+use std::f32::consts::PI;
+
+/// Parameters for [`tree`].
+pub struct PythagorasTree {
+    pub start: Point<Pixels>,
+    pub size: f32,
+    pub angle: f32,
+}
+
+impl Default for PythagorasTree {
+    fn default() -> Self {
+        PythagorasTree {
+            start: point(px(384.), px(700.)),
+            size: 200.0,
+            angle: PI / 2.0,
+        }
+    }
+}
+
+impl fractal::Fractal for PythagorasTree {
+    fn name(&self) -> &'static str {
+        "Pythagoras tree"
+    }
+
+    fn max_depth(&self) -> u32 {
+        16
+    }
+
+    fn default_depth(&self) -> u32 {
+        10
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        tree(self.start, self.size, self.angle, depth).into()
+    }
+
+    /// The trunk's base and length in pixels, and its direction in degrees.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("start.x", self.start.x.0 as f64),
+            ("start.y", self.start.y.0 as f64),
+            ("size", self.size as f64),
+            ("angle", self.angle.to_degrees() as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "start.x" => self.start.x = px(value as f32),
+            "start.y" => self.start.y = px(value as f32),
+            "size" => self.size = value as f32,
+            "angle" => self.angle = (value as f32).to_radians(),
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Grows a trunk of length `size` from `start` in direction `angle` (radians,
+/// counter-clockwise from the x axis), then two branches at +-45 degrees that
+/// are `1 / sqrt(2)` as long, `iterations` levels deep.
+pub fn tree(start: Point<Pixels>, size: f32, angle: f32, iterations: u32) -> Vec<Shape> {
+    let mut segments = Vec::new();
+    recursive(&mut segments, start, size, angle, iterations);
+    segments
+}
+
+fn recursive(
+    segments: &mut Vec<Shape>,
+    start: Point<Pixels>,
+    size: f32,
+    angle: f32,
+    iterations: u32,
+) {
+    if iterations == 0 {
+        return;
+    }
+
+    let end = Point {
+        x: start.x + px(size * angle.cos()),
+        y: start.y - px(size * angle.sin()),
+    };
+
+    segments.push(shapes::line(start, end).into());
+
+    let new_size = size / 2f32.sqrt();
+    let new_angle1 = angle + PI / 4.0;
+    let new_angle2 = angle - PI / 4.0;
+
+    recursive(segments, end, new_size, new_angle1, iterations - 1);
+    recursive(segments, end, new_size, new_angle2, iterations - 1);
+}
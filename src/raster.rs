@@ -0,0 +1,209 @@
+//! CPU-side RGBA images, for fractals that color every pixel. Painting one
+//! texture is far cheaper than painting a quad per pixel.
+
+use crate::shapes;
+use gpui::{point, px, Pixels, Point, RenderImage};
+use std::sync::Arc;
+
+/// Rasters up to this many pixels are cheap enough to paint as quads.
+pub const MAX_QUAD_PIXELS: usize = 128 * 128;
+
+/// Samples per pixel along each axis when filling triangles, so edges get
+/// sixteen levels of coverage.
+const SUBSAMPLES: usize = 4;
+
+/// A `width` x `height` image stored row by row as straight RGBA bytes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Raster {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// A fully transparent raster.
+    pub fn new(width: usize, height: usize) -> Self {
+        Raster {
+            width,
+            height,
+            pixels: vec![0; width * height * 4],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> [u8; 4] {
+        let offset = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[offset..offset + 4]);
+        pixel
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, rgba: [u8; 4]) {
+        let offset = (y * self.width + x) * 4;
+        self.pixels[offset..offset + 4].copy_from_slice(&rgba);
+    }
+
+    /// Copies `other` into this raster with its top-left corner at `(x, y)`.
+    /// `other` has to fit.
+    pub fn blit(&mut self, x: usize, y: usize, other: &Raster) {
+        let row_len = other.width * 4;
+        for row in 0..other.height {
+            let src = row * row_len;
+            let dst = ((y + row) * self.width + x) * 4;
+            self.pixels[dst..dst + row_len].copy_from_slice(&other.pixels[src..src + row_len]);
+        }
+    }
+
+    pub fn set_color(&mut self, x: usize, y: usize, color: impl Into<gpui::Rgba>) {
+        self.set(x, y, to_rgba8(color));
+    }
+
+    /// Paints `color` over pixel `(x, y)` at `opacity` times its own alpha.
+    pub fn blend(&mut self, x: usize, y: usize, color: gpui::Rgba, opacity: f32) {
+        let [r, g, b, a] = self.get(x, y).map(|channel| channel as f32 / 255.0);
+        let alpha = color.a * opacity;
+        let under = a * (1.0 - alpha);
+        let out = alpha + under;
+        if out <= 0.0 {
+            return;
+        }
+        let mix = |over: f32, channel: f32| (over * alpha + channel * under) / out;
+        self.set_color(
+            x,
+            y,
+            gpui::Rgba {
+                r: mix(color.r, r),
+                g: mix(color.g, g),
+                b: mix(color.b, b),
+                a: out,
+            },
+        );
+    }
+
+    /// Paints the area `triangles` cover in `color`, with antialiased edges.
+    /// The triangles count as one shape: where they share an edge or overlap,
+    /// the color is laid down once. Parts outside the raster are clipped.
+    pub fn fill_triangles(
+        &mut self,
+        triangles: &[[Point<Pixels>; 3]],
+        color: impl Into<gpui::Rgba>,
+    ) {
+        let color = color.into();
+        let mut min = point(f32::INFINITY, f32::INFINITY);
+        let mut max = point(f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for vertex in triangles.iter().flatten() {
+            min = point(min.x.min(vertex.x.0), min.y.min(vertex.y.0));
+            max = point(max.x.max(vertex.x.0), max.y.max(vertex.y.0));
+        }
+        let clip = |value: f32, limit: usize| (value.max(0.0) as usize).min(limit);
+        let (left, top) = (clip(min.x, self.width), clip(min.y, self.height));
+        let (right, bottom) = (
+            clip(max.x + 1.0, self.width),
+            clip(max.y + 1.0, self.height),
+        );
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        // One bit per sample, set once any triangle covers it.
+        let columns = right - left;
+        let mut coverage = vec![0u16; columns * (bottom - top)];
+        let scale = SUBSAMPLES as f32;
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|vertex| point(vertex.x.0, vertex.y.0));
+            let area = edge(a, b, c);
+            if area == 0.0 || !area.is_finite() {
+                continue;
+            }
+            let sample = |value: f32, origin: usize, limit: usize| {
+                (((value - origin as f32) * scale).max(0.0) as usize).min(limit * SUBSAMPLES)
+            };
+            let x0 = sample(a.x.min(b.x).min(c.x), left, columns);
+            let x1 = sample(a.x.max(b.x).max(c.x) + 1.0, left, columns);
+            let y0 = sample(a.y.min(b.y).min(c.y), top, bottom - top);
+            let y1 = sample(a.y.max(b.y).max(c.y) + 1.0, top, bottom - top);
+            for sy in y0..y1 {
+                let y = top as f32 + (sy as f32 + 0.5) / scale;
+                for sx in x0..x1 {
+                    let p = point(left as f32 + (sx as f32 + 0.5) / scale, y);
+                    // Inside when p is on the same side of every edge as the
+                    // triangle itself, whichever way it winds.
+                    let inside = edge(b, c, p) * area >= 0.0
+                        && edge(c, a, p) * area >= 0.0
+                        && edge(a, b, p) * area >= 0.0;
+                    if inside {
+                        let bit = (sy % SUBSAMPLES) * SUBSAMPLES + sx % SUBSAMPLES;
+                        coverage[sy / SUBSAMPLES * columns + sx / SUBSAMPLES] |= 1 << bit;
+                    }
+                }
+            }
+        }
+
+        let samples = (SUBSAMPLES * SUBSAMPLES) as f32;
+        for (ix, bits) in coverage.into_iter().enumerate() {
+            if bits != 0 {
+                let opacity = bits.count_ones() as f32 / samples;
+                self.blend(left + ix % columns, top + ix / columns, color, opacity);
+            }
+        }
+    }
+
+    /// One pixel quad per non-transparent pixel, for painting small rasters
+    /// without uploading a texture.
+    pub fn quads(&self) -> Vec<gpui::PaintQuad> {
+        let mut quads = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let [r, g, b, a] = self.get(x, y);
+                if a == 0 {
+                    continue;
+                }
+                let color = gpui::Rgba {
+                    r: r as f32 / 255.0,
+                    g: g as f32 / 255.0,
+                    b: b as f32 / 255.0,
+                    a: a as f32 / 255.0,
+                };
+                let pixel = shapes::pixel(point(px(x as f32), px(y as f32))).color(color);
+                quads.push(pixel.quad());
+            }
+        }
+        quads
+    }
+
+    /// Writes the raster to `path` as a PNG.
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> image::ImageResult<()> {
+        image::save_buffer_with_format(
+            path,
+            &self.pixels,
+            self.width as u32,
+            self.height as u32,
+            image::ExtendedColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+    }
+
+    /// Copies the raster into an image `Window::paint_image` can draw.
+    pub fn render_image(&self) -> Arc<RenderImage> {
+        // gpui expects BGRA textures.
+        let mut pixels = self.pixels.clone();
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+        let buffer = image::RgbaImage::from_raw(self.width as u32, self.height as u32, pixels)
+            .expect("raster size matches its pixel buffer");
+        Arc::new(RenderImage::new(vec![image::Frame::new(buffer)]))
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`: positive on one side
+/// of the line through `a` and `b`, negative on the other.
+fn edge(a: Point<f32>, b: Point<f32>, p: Point<f32>) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Converts a color to straight RGBA bytes, clamping out-of-range channels.
+pub fn to_rgba8(color: impl Into<gpui::Rgba>) -> [u8; 4] {
+    let rgba = color.into();
+    let byte = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+    [byte(rgba.r), byte(rgba.g), byte(rgba.b), byte(rgba.a)]
+}
@@ -0,0 +1,56 @@
+//! A small seeded random number generator, so randomized fractals come out
+//! the same every time for the same seed.
+
+/// SplitMix64: fast, tiny and good enough for picking rules and points.
+/// Not for anything that needs to be unpredictable.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..1`.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Uniform in `0..1`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// The index of one of `weights`, each chosen in proportion to its
+    /// weight. Returns `None` if there are no positive weights.
+    pub fn weighted(&mut self, weights: impl IntoIterator<Item = f32> + Clone) -> Option<usize> {
+        let total: f32 = weights.clone().into_iter().map(|w| w.max(0.0)).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut target = self.next_f32() * total;
+        let mut last = None;
+        for (ix, weight) in weights.into_iter().enumerate() {
+            if weight <= 0.0 {
+                continue;
+            }
+            if target < weight {
+                return Some(ix);
+            }
+            target -= weight;
+            last = Some(ix);
+        }
+        // Rounding can leave a sliver past the last weight.
+        last
+    }
+}
@@ -0,0 +1,854 @@
+//! Small drawing primitives with a fluent builder API.
+//!
+//! Circles and pixels become [`gpui::PaintQuad`]s via `quad()`; lines,
+//! triangles, polygons and polylines become `(gpui::Path, gpui::Hsla)` pairs
+//! via `paint()`, ready for `Window::paint_path`. Curves (beziers, arcs and
+//! ellipses) are flattened into polylines and polygons up front. Outlines and fills are
+//! tessellated with lyon, so stroke width, joins, caps and fill rules are
+//! honored. Collected as [`Shape`]s, they keep their geometry around for
+//! vector output and offscreen rasterizing.
+
+use gpui::*;
+pub use lyon::tessellation::{FillRule, LineCap, LineJoin};
+use lyon::{
+    geom::{self, Angle},
+    math, path as lyon_path,
+    tessellation::{
+        BuffersBuilder, FillOptions, FillTessellator, FillVertex, StrokeOptions, StrokeTessellator,
+        StrokeVertex, VertexBuffers,
+    },
+};
+
+/// How far flattened curves may stray from the true curve, in pixels.
+pub const CURVE_TOLERANCE: f32 = 0.1;
+
+/// Outline settings shared by every shape. Defaults to a 1px stroke with
+/// butt caps and miter joins, like SVG.
+#[derive(Clone, Copy, Debug)]
+pub struct Stroke {
+    width: Pixels,
+    color: gpui::Hsla,
+    line_join: LineJoin,
+    line_cap: LineCap,
+    miter_limit: f32,
+}
+
+pub struct ShapeProperties {
+    pub fill: Background,
+    pub position: Point<Pixels>,
+    pub size: Pixels,
+    pub stroke: Stroke,
+}
+
+impl Stroke {
+    pub fn width(&self) -> Pixels {
+        self.width
+    }
+
+    pub fn color(&self) -> gpui::Hsla {
+        self.color
+    }
+
+    pub fn line_join(&self) -> LineJoin {
+        self.line_join
+    }
+
+    pub fn line_cap(&self) -> LineCap {
+        self.line_cap
+    }
+
+    pub fn miter_limit(&self) -> f32 {
+        self.miter_limit
+    }
+
+    /// Tessellates the outline through `points` into a path of triangles.
+    /// `closed` joins the last point back to the first instead of capping
+    /// both ends. A zero width, or fewer than two points, gives an empty path.
+    pub fn tessellate(&self, points: &[Point<Pixels>], closed: bool) -> gpui::Path<Pixels> {
+        to_path(points, &self.triangles(points, closed))
+    }
+
+    /// The triangles [`Stroke::tessellate`] builds its path from.
+    pub fn triangles(&self, points: &[Point<Pixels>], closed: bool) -> Vec<[Point<Pixels>; 3]> {
+        if points.len() < 2 || self.width <= px(0.) {
+            return Vec::new();
+        }
+
+        let points = to_lyon(points);
+        let options = StrokeOptions::default()
+            .with_line_width(self.width.0)
+            .with_line_join(self.line_join)
+            .with_line_cap(self.line_cap)
+            .with_miter_limit(self.miter_limit);
+        let mut geometry: VertexBuffers<math::Point, u32> = VertexBuffers::new();
+        let result = StrokeTessellator::new().tessellate_polygon(
+            lyon_path::Polygon {
+                points: &points,
+                closed,
+            },
+            &options,
+            &mut BuffersBuilder::new(&mut geometry, |vertex: StrokeVertex| vertex.position()),
+        );
+        match result {
+            Ok(()) => triangles(&geometry),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the outline would paint anything.
+    pub fn is_visible(&self) -> bool {
+        self.width > px(0.) && self.color.a > 0.
+    }
+}
+
+/// Tessellates the inside of the closed outline through `points` into a path
+/// of triangles, deciding what counts as inside by `rule`.
+pub fn tessellate_fill(points: &[Point<Pixels>], rule: FillRule) -> gpui::Path<Pixels> {
+    to_path(points, &fill_triangles(points, rule))
+}
+
+/// The triangles [`tessellate_fill`] builds its path from.
+pub fn fill_triangles(points: &[Point<Pixels>], rule: FillRule) -> Vec<[Point<Pixels>; 3]> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+
+    let points = to_lyon(points);
+    let polygon = lyon_path::Polygon {
+        points: &points,
+        closed: true,
+    };
+    let mut geometry: VertexBuffers<math::Point, u32> = VertexBuffers::new();
+    let result = FillTessellator::new().tessellate(
+        polygon.path_events(),
+        &FillOptions::default().with_fill_rule(rule),
+        &mut BuffersBuilder::new(&mut geometry, |vertex: FillVertex| vertex.position()),
+    );
+    match result {
+        Ok(()) => triangles(&geometry),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn to_lyon(points: &[Point<Pixels>]) -> Vec<math::Point> {
+    points.iter().copied().map(to_lyon_point).collect()
+}
+
+fn to_lyon_point(point: Point<Pixels>) -> math::Point {
+    math::point(point.x.0, point.y.0)
+}
+
+fn from_lyon_point(point: math::Point) -> Point<Pixels> {
+    gpui::point(px(point.x), px(point.y))
+}
+
+fn triangles(geometry: &VertexBuffers<math::Point, u32>) -> Vec<[Point<Pixels>; 3]> {
+    let vertex = |ix: u32| from_lyon_point(geometry.vertices[ix as usize]);
+    geometry
+        .indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            [
+                vertex(triangle[0]),
+                vertex(triangle[1]),
+                vertex(triangle[2]),
+            ]
+        })
+        .collect()
+}
+
+/// A path draws a triangle for every `line_to` after the first one in a
+/// contour, so each triangle becomes its own contour. The path starts at the
+/// first of `points`, the outline the triangles were tessellated from.
+fn to_path(points: &[Point<Pixels>], triangles: &[[Point<Pixels>; 3]]) -> gpui::Path<Pixels> {
+    let mut path = Path::new(points.first().copied().unwrap_or_default());
+    for [p1, p2, p3] in triangles {
+        path.move_to(*p1);
+        path.line_to(*p2);
+        path.line_to(*p3);
+    }
+    path
+}
+
+impl From<Hsla> for Stroke {
+    fn from(color: Hsla) -> Self {
+        Stroke {
+            width: px(1.),
+            color,
+            line_join: StrokeOptions::DEFAULT_LINE_JOIN,
+            line_cap: StrokeOptions::DEFAULT_LINE_CAP,
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+        }
+    }
+}
+
+/// A white-outlined circle of `radius` centered on `position`.
+pub fn circle(radius: impl Into<Pixels>, position: Point<Pixels>) -> Circle {
+    Circle::new(radius.into(), position)
+}
+
+/// A single white 1x1 quad with its top-left corner at `position`.
+pub fn pixel(position: Point<Pixels>) -> Pixel {
+    Pixel::new(position)
+}
+
+/// A white 1px line from `start` to `end`.
+pub fn line(start: Point<Pixels>, end: Point<Pixels>) -> Line {
+    Line::new(start, end)
+}
+
+/// A white 1px triangle outline through the three points.
+pub fn triangle(p1: Point<Pixels>, p2: Point<Pixels>, p3: Point<Pixels>) -> Triangle {
+    Triangle::new(p1, p2, p3)
+}
+
+/// A white 1px outline through `points`, closed back to the first one.
+pub fn polygon(points: impl IntoIterator<Item = Point<Pixels>>) -> Polygon {
+    Polygon::new(points.into_iter().collect())
+}
+
+/// A white 1px line through `points`, left open.
+pub fn polyline(points: impl IntoIterator<Item = Point<Pixels>>) -> Polyline {
+    Polyline::new(points.into_iter().collect())
+}
+
+/// A quadratic bezier curve from `from` to `to`, bending towards `ctrl`.
+pub fn quadratic_bezier(from: Point<Pixels>, ctrl: Point<Pixels>, to: Point<Pixels>) -> Polyline {
+    let curve = geom::QuadraticBezierSegment {
+        from: to_lyon_point(from),
+        ctrl: to_lyon_point(ctrl),
+        to: to_lyon_point(to),
+    };
+    polyline(std::iter::once(from).chain(curve.flattened(CURVE_TOLERANCE).map(from_lyon_point)))
+}
+
+/// A cubic bezier curve from `from` to `to`, leaving towards `ctrl1` and
+/// arriving from `ctrl2`.
+pub fn cubic_bezier(
+    from: Point<Pixels>,
+    ctrl1: Point<Pixels>,
+    ctrl2: Point<Pixels>,
+    to: Point<Pixels>,
+) -> Polyline {
+    let curve = geom::CubicBezierSegment {
+        from: to_lyon_point(from),
+        ctrl1: to_lyon_point(ctrl1),
+        ctrl2: to_lyon_point(ctrl2),
+        to: to_lyon_point(to),
+    };
+    polyline(std::iter::once(from).chain(curve.flattened(CURVE_TOLERANCE).map(from_lyon_point)))
+}
+
+/// A circular arc around `center`, starting `start_angle` radians clockwise
+/// from the positive x axis and sweeping `sweep_angle` radians further
+/// clockwise. Negative sweeps go counter-clockwise.
+pub fn arc(
+    center: Point<Pixels>,
+    radius: impl Into<Pixels>,
+    start_angle: f32,
+    sweep_angle: f32,
+) -> Polyline {
+    let radius = radius.into();
+    polyline(arc_points(
+        center,
+        size(radius, radius),
+        start_angle,
+        sweep_angle,
+    ))
+}
+
+/// A white 1px ellipse outline around `center`, `radii.width` across and
+/// `radii.height` down from it.
+pub fn ellipse(center: Point<Pixels>, radii: Size<Pixels>) -> Polygon {
+    let mut points = arc_points(center, radii, 0., std::f32::consts::TAU);
+    // The last point lands back on the first; the polygon closes itself.
+    points.pop();
+    Polygon::new(points)
+}
+
+fn arc_points(
+    center: Point<Pixels>,
+    radii: Size<Pixels>,
+    start_angle: f32,
+    sweep_angle: f32,
+) -> Vec<Point<Pixels>> {
+    let arc = geom::Arc {
+        center: to_lyon_point(center),
+        radii: math::vector(radii.width.0, radii.height.0),
+        start_angle: Angle::radians(start_angle),
+        sweep_angle: Angle::radians(sweep_angle),
+        x_rotation: Angle::zero(),
+    };
+    std::iter::once(arc.from())
+        .chain(arc.flattened(CURVE_TOLERANCE))
+        .map(from_lyon_point)
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Circle {
+    fill: Option<gpui::Hsla>,
+    position: Point<Pixels>,
+    size: Pixels,
+    stroke: Stroke,
+}
+
+pub struct Pixel {
+    position: Point<Pixels>,
+    color: gpui::Hsla,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Line {
+    start: Point<Pixels>,
+    end: Point<Pixels>,
+    stroke: Stroke,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Triangle {
+    p1: Point<Pixels>,
+    p2: Point<Pixels>,
+    p3: Point<Pixels>,
+    stroke: Stroke,
+    fill: Option<gpui::Hsla>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    points: Vec<Point<Pixels>>,
+    stroke: Stroke,
+    fill: Option<gpui::Hsla>,
+    fill_rule: FillRule,
+}
+
+#[derive(Clone, Debug)]
+pub struct Polyline {
+    points: Vec<Point<Pixels>>,
+    stroke: Stroke,
+    fill: Option<gpui::Hsla>,
+}
+
+impl Circle {
+    pub fn new(radius: Pixels, position: Point<Pixels>) -> Self {
+        Circle {
+            stroke: gpui::white().into(),
+            fill: None,
+            size: radius * 2.0,
+            position,
+        }
+    }
+
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke.width = width.into();
+        self
+    }
+
+    pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.stroke.color = color.into();
+        self
+    }
+
+    pub fn fill(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.fill = Some(color.into());
+        self
+    }
+
+    pub fn no_stroke(mut self) -> Self {
+        self.stroke.width = px(0.);
+        self
+    }
+
+    pub fn quad(&self) -> gpui::PaintQuad {
+        let center = point(self.position.x, self.position.y);
+
+        let bounds = Bounds::centered_at(
+            center,
+            Size {
+                width: self.size,
+                height: self.size,
+            },
+        );
+        let background = self.fill.unwrap_or_else(gpui::transparent_black).into();
+        let border_color = self.stroke.color;
+        let border_width = self.stroke.width;
+        let corner_radii = Corners::all(self.size / 2.0);
+
+        gpui::PaintQuad {
+            bounds,
+            corner_radii,
+            background,
+            border_widths: Edges::all(border_width),
+            border_color,
+        }
+    }
+
+    pub fn center(&self) -> Point<Pixels> {
+        self.position
+    }
+
+    pub fn radius(&self) -> Pixels {
+        self.size / 2.0
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    /// The circle flattened into a closed outline, pulled in by half the
+    /// stroke width so the stroke stays inside the circle, like the border of
+    /// [`Circle::quad`] does.
+    pub fn outline(&self) -> Vec<Point<Pixels>> {
+        let radius = px((self.radius().0 - self.stroke.width.0 / 2.0).max(0.));
+        ellipse(self.position, size(radius, radius)).points
+    }
+
+    /// The fill, if any, then the outline, if visible, as paths. Painting
+    /// [`Circle::quad`] instead is much cheaper.
+    pub fn paint(&self) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+        paint_outline(
+            &self.outline(),
+            true,
+            self.fill,
+            FillRule::NonZero,
+            &self.stroke,
+        )
+    }
+}
+
+impl Pixel {
+    pub fn new(position: Point<Pixels>) -> Self {
+        Pixel {
+            position,
+            color: gpui::white(),
+        }
+    }
+
+    pub fn color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    pub fn quad(&self) -> gpui::PaintQuad {
+        let bounds = Bounds::from_corner_and_size(
+            gpui::Corner::TopLeft,
+            self.position,
+            Size {
+                width: px(1.),
+                height: px(1.),
+            },
+        );
+        let background = self.color.into();
+
+        gpui::PaintQuad {
+            bounds,
+            corner_radii: Corners::default(),
+            background,
+            border_widths: Edges::default(),
+            border_color: gpui::transparent_black(),
+        }
+    }
+}
+
+impl Line {
+    pub fn new(start: Point<Pixels>, end: Point<Pixels>) -> Self {
+        Line {
+            start,
+            end,
+            stroke: gpui::white().into(),
+        }
+    }
+
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke.width = width.into();
+        self
+    }
+
+    pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.stroke.color = color.into();
+        self
+    }
+
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        self.stroke.line_join = line_join;
+        self
+    }
+
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        self.stroke.line_cap = line_cap;
+        self
+    }
+
+    /// The longest a miter join may get, as a multiple of the stroke width,
+    /// before it is beveled instead. Clamped to at least 1.
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.stroke.miter_limit = miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT);
+        self
+    }
+
+    pub fn start(&self) -> Point<Pixels> {
+        self.start
+    }
+
+    pub fn end(&self) -> Point<Pixels> {
+        self.end
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    pub fn paint(&self) -> (gpui::Path<Pixels>, gpui::Hsla) {
+        let path = self.stroke.tessellate(&[self.start, self.end], false);
+        (path, self.stroke.color)
+    }
+}
+
+impl Triangle {
+    pub fn new(p1: Point<Pixels>, p2: Point<Pixels>, p3: Point<Pixels>) -> Self {
+        Triangle {
+            p1,
+            p2,
+            p3,
+            stroke: gpui::white().into(),
+            fill: None,
+        }
+    }
+
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke.width = width.into();
+        self
+    }
+
+    pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.stroke.color = color.into();
+        self
+    }
+
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        self.stroke.line_join = line_join;
+        self
+    }
+
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        self.stroke.line_cap = line_cap;
+        self
+    }
+
+    /// The longest a miter join may get, as a multiple of the stroke width,
+    /// before it is beveled instead. Clamped to at least 1.
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.stroke.miter_limit = miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT);
+        self
+    }
+
+    pub fn fill(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.fill = Some(color.into());
+        self
+    }
+
+    pub fn no_stroke(mut self) -> Self {
+        self.stroke.width = px(0.);
+        self
+    }
+
+    pub fn points(&self) -> [Point<Pixels>; 3] {
+        [self.p1, self.p2, self.p3]
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    /// The fill, if any, then the outline, if visible.
+    pub fn paint(&self) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+        paint_outline(
+            &self.points(),
+            true,
+            self.fill,
+            FillRule::NonZero,
+            &self.stroke,
+        )
+    }
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point<Pixels>>) -> Self {
+        Polygon {
+            points,
+            stroke: gpui::white().into(),
+            fill: None,
+            fill_rule: FillRule::NonZero,
+        }
+    }
+
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke.width = width.into();
+        self
+    }
+
+    pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.stroke.color = color.into();
+        self
+    }
+
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        self.stroke.line_join = line_join;
+        self
+    }
+
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        self.stroke.line_cap = line_cap;
+        self
+    }
+
+    /// The longest a miter join may get, as a multiple of the stroke width,
+    /// before it is beveled instead. Clamped to at least 1.
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.stroke.miter_limit = miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT);
+        self
+    }
+
+    pub fn fill(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.fill = Some(color.into());
+        self
+    }
+
+    /// Decides which parts of a self-intersecting outline are filled.
+    /// Defaults to [`FillRule::NonZero`], like SVG.
+    pub fn fill_rule(mut self, fill_rule: FillRule) -> Self {
+        self.fill_rule = fill_rule;
+        self
+    }
+
+    pub fn no_stroke(mut self) -> Self {
+        self.stroke.width = px(0.);
+        self
+    }
+
+    pub fn points(&self) -> &[Point<Pixels>] {
+        &self.points
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    /// The fill, if any, then the outline, if visible.
+    pub fn paint(&self) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+        paint_outline(&self.points, true, self.fill, self.fill_rule, &self.stroke)
+    }
+}
+
+impl Polyline {
+    pub fn new(points: Vec<Point<Pixels>>) -> Self {
+        Polyline {
+            points,
+            stroke: gpui::white().into(),
+            fill: None,
+        }
+    }
+
+    pub fn stroke_width(mut self, width: impl Into<Pixels>) -> Self {
+        self.stroke.width = width.into();
+        self
+    }
+
+    pub fn stroke_color(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.stroke.color = color.into();
+        self
+    }
+
+    pub fn line_join(mut self, line_join: LineJoin) -> Self {
+        self.stroke.line_join = line_join;
+        self
+    }
+
+    pub fn line_cap(mut self, line_cap: LineCap) -> Self {
+        self.stroke.line_cap = line_cap;
+        self
+    }
+
+    /// The longest a miter join may get, as a multiple of the stroke width,
+    /// before it is beveled instead. Clamped to at least 1.
+    pub fn miter_limit(mut self, miter_limit: f32) -> Self {
+        self.stroke.miter_limit = miter_limit.max(StrokeOptions::MINIMUM_MITER_LIMIT);
+        self
+    }
+
+    /// Fills the area between the line and the straight segment from its end
+    /// back to its start, like SVG does for open paths.
+    pub fn fill(mut self, color: impl Into<gpui::Hsla>) -> Self {
+        self.fill = Some(color.into());
+        self
+    }
+
+    pub fn no_stroke(mut self) -> Self {
+        self.stroke.width = px(0.);
+        self
+    }
+
+    /// Continues the line through `points`.
+    pub fn extend(mut self, points: impl IntoIterator<Item = Point<Pixels>>) -> Self {
+        self.points.extend(points);
+        self
+    }
+
+    pub fn points(&self) -> &[Point<Pixels>] {
+        &self.points
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        self.stroke
+    }
+
+    /// The fill, if any, then the line, if visible.
+    pub fn paint(&self) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+        paint_outline(
+            &self.points,
+            false,
+            self.fill,
+            FillRule::NonZero,
+            &self.stroke,
+        )
+    }
+}
+
+fn paint_outline(
+    points: &[Point<Pixels>],
+    closed: bool,
+    fill: Option<gpui::Hsla>,
+    fill_rule: FillRule,
+    stroke: &Stroke,
+) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+    outline_triangles(points, closed, fill, fill_rule, stroke)
+        .into_iter()
+        .map(|(triangles, color)| (to_path(points, &triangles), color))
+        .collect()
+}
+
+fn outline_triangles(
+    points: &[Point<Pixels>],
+    closed: bool,
+    fill: Option<gpui::Hsla>,
+    fill_rule: FillRule,
+    stroke: &Stroke,
+) -> Vec<(Vec<[Point<Pixels>; 3]>, gpui::Hsla)> {
+    let mut triangles = Vec::new();
+    if let Some(fill) = fill {
+        triangles.push((fill_triangles(points, fill_rule), fill));
+    }
+    if stroke.is_visible() {
+        triangles.push((stroke.triangles(points, closed), stroke.color));
+    }
+    triangles
+}
+
+/// A shape kept as geometry, so it can be painted or written out as vector
+/// data later.
+#[derive(Clone, Debug)]
+pub enum Shape {
+    Circle(Circle),
+    Line(Line),
+    Triangle(Triangle),
+    Polygon(Polygon),
+    Polyline(Polyline),
+}
+
+impl Shape {
+    /// The corners of the shape, in drawing order.
+    pub fn points(&self) -> Vec<Point<Pixels>> {
+        match self {
+            Shape::Circle(circle) => circle.outline(),
+            Shape::Line(line) => vec![line.start, line.end],
+            Shape::Triangle(triangle) => triangle.points().to_vec(),
+            Shape::Polygon(polygon) => polygon.points.clone(),
+            Shape::Polyline(polyline) => polyline.points.clone(),
+        }
+    }
+
+    /// Whether the outline returns to its first point.
+    pub fn is_closed(&self) -> bool {
+        !matches!(self, Shape::Line(_) | Shape::Polyline(_))
+    }
+
+    pub fn stroke(&self) -> Stroke {
+        match self {
+            Shape::Circle(circle) => circle.stroke,
+            Shape::Line(line) => line.stroke,
+            Shape::Triangle(triangle) => triangle.stroke,
+            Shape::Polygon(polygon) => polygon.stroke,
+            Shape::Polyline(polyline) => polyline.stroke,
+        }
+    }
+
+    pub fn fill(&self) -> Option<gpui::Hsla> {
+        match self {
+            Shape::Circle(circle) => circle.fill,
+            Shape::Line(_) => None,
+            Shape::Triangle(triangle) => triangle.fill,
+            Shape::Polygon(polygon) => polygon.fill,
+            Shape::Polyline(polyline) => polyline.fill,
+        }
+    }
+
+    pub fn fill_rule(&self) -> FillRule {
+        match self {
+            Shape::Polygon(polygon) => polygon.fill_rule,
+            _ => FillRule::NonZero,
+        }
+    }
+
+    /// Every path the shape paints, fills before outlines.
+    pub fn paint(&self) -> Vec<(gpui::Path<Pixels>, gpui::Hsla)> {
+        match self {
+            Shape::Circle(circle) => circle.paint(),
+            Shape::Line(line) => vec![line.paint()],
+            Shape::Triangle(triangle) => triangle.paint(),
+            Shape::Polygon(polygon) => polygon.paint(),
+            Shape::Polyline(polyline) => polyline.paint(),
+        }
+    }
+
+    /// What [`Shape::paint`] paints, as plain triangles for rasterizing
+    /// without gpui.
+    pub fn triangles(&self) -> Vec<(Vec<[Point<Pixels>; 3]>, gpui::Hsla)> {
+        outline_triangles(
+            &self.points(),
+            self.is_closed(),
+            self.fill(),
+            self.fill_rule(),
+            &self.stroke(),
+        )
+    }
+}
+
+impl From<Circle> for Shape {
+    fn from(circle: Circle) -> Self {
+        Shape::Circle(circle)
+    }
+}
+
+impl From<Line> for Shape {
+    fn from(line: Line) -> Self {
+        Shape::Line(line)
+    }
+}
+
+impl From<Triangle> for Shape {
+    fn from(triangle: Triangle) -> Self {
+        Shape::Triangle(triangle)
+    }
+}
+
+impl From<Polygon> for Shape {
+    fn from(polygon: Polygon) -> Self {
+        Shape::Polygon(polygon)
+    }
+}
+
+impl From<Polyline> for Shape {
+    fn from(polyline: Polyline) -> Self {
+        Shape::Polyline(polyline)
+    }
+}
@@ -0,0 +1,119 @@
+//! The Sierpinski triangle.
+
+use crate::{
+    fractal,
+    shapes::{self, Shape},
+};
+use gpui::{point, px, Pixels, Point};
+
+/// Parameters for [`triangle`] and [`tiles`].
+pub struct SierpinskiTriangle {
+    pub start: Point<Pixels>,
+    pub side_length: f32,
+    /// Draw solid tiles instead of outlines.
+    pub filled: bool,
+}
+
+impl Default for SierpinskiTriangle {
+    fn default() -> Self {
+        SierpinskiTriangle {
+            start: point(px(134.), px(167.)),
+            side_length: 500.0,
+            filled: true,
+        }
+    }
+}
+
+impl fractal::Fractal for SierpinskiTriangle {
+    fn name(&self) -> &'static str {
+        "Sierpinski triangle"
+    }
+
+    fn max_depth(&self) -> u32 {
+        10
+    }
+
+    fn default_depth(&self) -> u32 {
+        6
+    }
+
+    fn draw(&self, depth: u32) -> fractal::FractalOutput {
+        if self.filled {
+            tiles(self.start, self.side_length, depth).into()
+        } else {
+            triangle(self.start, self.side_length, depth).into()
+        }
+    }
+
+    /// The top-left corner and side of the outer triangle, in pixels.
+    fn parameters(&self) -> Vec<(&'static str, f64)> {
+        vec![
+            ("start.x", self.start.x.0 as f64),
+            ("start.y", self.start.y.0 as f64),
+            ("side_length", self.side_length as f64),
+        ]
+    }
+
+    fn set_parameter(&mut self, name: &str, value: f64) -> bool {
+        match name {
+            "start.x" => self.start.x = px(value as f32),
+            "start.y" => self.start.y = px(value as f32),
+            "side_length" => self.side_length = value as f32,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Splits the equilateral triangle at `start` into three half-size copies,
+/// `iterations` times, and outlines the `3^iterations` leaves.
+pub fn triangle(start: Point<Pixels>, side_length: f32, iterations: u32) -> Vec<Shape> {
+    let mut triangles = Vec::new();
+    recursive(&mut triangles, start, side_length, iterations, false);
+    triangles
+}
+
+/// Like [`triangle`], but the leaves are filled white instead of outlined.
+pub fn tiles(start: Point<Pixels>, side_length: f32, iterations: u32) -> Vec<Shape> {
+    let mut triangles = Vec::new();
+    recursive(&mut triangles, start, side_length, iterations, true);
+    triangles
+}
+
+fn recursive(
+    triangles: &mut Vec<Shape>,
+    start: Point<Pixels>,
+    side_length: f32,
+    iterations: u32,
+    filled: bool,
+) {
+    if iterations == 0 {
+        let height = side_length * 3f32.sqrt() / 2.0;
+        let p1 = start;
+        let p2 = start + point(px(side_length), px(0.0));
+        let p3 = start + point(px(side_length / 2.0), px(height));
+        let triangle = shapes::triangle(p1, p2, p3);
+        if filled {
+            triangles.push(triangle.fill(gpui::white()).no_stroke().into());
+        } else {
+            triangles.push(triangle.into());
+        }
+    } else {
+        let new_side = side_length / 2.0;
+        recursive(triangles, start, new_side, iterations - 1, filled);
+        recursive(
+            triangles,
+            start + point(px(new_side), px(0.0)),
+            new_side,
+            iterations - 1,
+            filled,
+        );
+        recursive(
+            triangles,
+            start + point(px(new_side / 2.0), px(new_side * 3f32.sqrt() / 2.0)),
+            new_side,
+            iterations - 1,
+            filled,
+        );
+    }
+}
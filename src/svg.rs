@@ -0,0 +1,123 @@
+//! Writes [`Shape`]s out as an SVG document, so vector fractals can be
+//! exported at any resolution.
+
+use crate::{
+    raster,
+    shapes::{FillRule, LineCap, LineJoin, Shape},
+};
+use gpui::Point;
+use std::fmt::Write as _;
+
+/// An SVG document with one `<path>` per shape. The view box is fitted to the
+/// shapes, with room for their strokes.
+pub fn document(shapes: &[Shape]) -> String {
+    let (min, max) = bounds(shapes);
+    let width = max.x - min.x;
+    let height = max.y - min.y;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+        min.x, min.y, width, height, width, height
+    )
+    .unwrap();
+    for shape in shapes {
+        writeln!(svg, "  {}", path(shape)).unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes [`document`] to `path`.
+pub fn save(shapes: &[Shape], path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    std::fs::write(path, document(shapes))
+}
+
+fn path(shape: &Shape) -> String {
+    let mut d = String::new();
+    for (ix, point) in shape.points().into_iter().enumerate() {
+        let command = if ix == 0 { 'M' } else { 'L' };
+        write!(d, "{command}{} {} ", point.x.0, point.y.0).unwrap();
+    }
+    if shape.is_closed() {
+        d.push('Z');
+    }
+
+    let mut element = format!(r#"<path d="{}""#, d.trim_end());
+    match shape.fill() {
+        Some(fill) => {
+            color(&mut element, "fill", fill);
+            if shape.fill_rule() == FillRule::EvenOdd {
+                element.push_str(r#" fill-rule="evenodd""#);
+            }
+        }
+        None => element.push_str(r#" fill="none""#),
+    }
+
+    let stroke = shape.stroke();
+    if !stroke.is_visible() {
+        element.push_str(r#" stroke="none"/>"#);
+        return element;
+    }
+    color(&mut element, "stroke", stroke.color());
+    write!(element, r#" stroke-width="{}""#, stroke.width().0).unwrap();
+    let line_join = match stroke.line_join() {
+        LineJoin::Miter => None,
+        LineJoin::MiterClip => Some("miter-clip"),
+        LineJoin::Round => Some("round"),
+        LineJoin::Bevel => Some("bevel"),
+    };
+    if let Some(line_join) = line_join {
+        write!(element, r#" stroke-linejoin="{line_join}""#).unwrap();
+    }
+    let line_cap = match stroke.line_cap() {
+        LineCap::Butt => None,
+        LineCap::Square => Some("square"),
+        LineCap::Round => Some("round"),
+    };
+    if let Some(line_cap) = line_cap {
+        write!(element, r#" stroke-linecap="{line_cap}""#).unwrap();
+    }
+    if stroke.miter_limit() != 4.0 {
+        write!(element, r#" stroke-miterlimit="{}""#, stroke.miter_limit()).unwrap();
+    }
+    element.push_str("/>");
+    element
+}
+
+/// Appends `attribute` set to `value`'s RGB, plus an opacity attribute if it
+/// isn't opaque.
+fn color(element: &mut String, attribute: &str, value: gpui::Hsla) {
+    let [r, g, b, a] = raster::to_rgba8(value);
+    write!(element, r##" {attribute}="#{r:02x}{g:02x}{b:02x}""##).unwrap();
+    if a < 255 {
+        write!(element, r#" {attribute}-opacity="{}""#, a as f32 / 255.0).unwrap();
+    }
+}
+
+/// The smallest box around every point, grown by half the widest stroke so
+/// outlines aren't clipped. Falls back to a unit box when there's nothing to
+/// draw.
+pub(crate) fn bounds(shapes: &[Shape]) -> (Point<f32>, Point<f32>) {
+    let mut min = Point::new(f32::INFINITY, f32::INFINITY);
+    let mut max = Point::new(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    let mut stroke_width = 0.0f32;
+    for shape in shapes {
+        for point in shape.points() {
+            let (x, y) = (point.x.0, point.y.0);
+            min = Point::new(min.x.min(x), min.y.min(y));
+            max = Point::new(max.x.max(x), max.y.max(y));
+        }
+        stroke_width = stroke_width.max(shape.stroke().width().0);
+    }
+
+    if min.x > max.x {
+        return (Point::new(0.0, 0.0), Point::new(1.0, 1.0));
+    }
+    let margin = stroke_width / 2.0;
+    (
+        Point::new(min.x - margin, min.y - margin),
+        Point::new(max.x + margin, max.y + margin),
+    )
+}
@@ -0,0 +1,116 @@
+//! The mapping between canvas pixels and the complex plane, shared by the
+//! escape-time fractals so a view can pan and zoom them.
+
+use crate::double_double::DoubleDouble;
+use num_complex::Complex;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    /// Canvas size in pixels.
+    pub width: usize,
+    pub height: usize,
+    /// The point of the plane shown in the middle of the canvas.
+    pub center: Complex<f64>,
+    /// What `center` was rounded off by. Zooming and panning keep track of it
+    /// so deep zooms can place the center more finely than an f64 can.
+    pub center_low: Complex<f64>,
+    /// Distance on the plane from `center` to the nearest canvas edge.
+    pub scale: f64,
+    /// How far the view is turned about `center`, in radians. Positive angles
+    /// turn the picture counterclockwise on screen.
+    pub rotation: f64,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(768, 768)
+    }
+}
+
+impl Viewport {
+    /// A `width` x `height` canvas showing `[-2, 2]` along its shorter side.
+    pub fn new(width: usize, height: usize) -> Self {
+        Viewport {
+            width,
+            height,
+            center: Complex::new(0.0, 0.0),
+            center_low: Complex::new(0.0, 0.0),
+            scale: 2.0,
+            rotation: 0.0,
+        }
+    }
+
+    /// How much of the plane one canvas pixel covers.
+    pub fn units_per_pixel(&self) -> f64 {
+        2.0 * self.scale / self.width.min(self.height).max(1) as f64
+    }
+
+    /// The point of the plane under canvas position `(x, y)`. The imaginary axis
+    /// grows downwards, like canvas y.
+    pub fn to_complex(&self, x: f64, y: f64) -> Complex<f64> {
+        self.center + self.offset(x, y)
+    }
+
+    /// How far the point under canvas position `(x, y)` is from `center`.
+    /// Unlike [`Viewport::to_complex`] this stays accurate however deep the
+    /// zoom.
+    pub fn offset(&self, x: f64, y: f64) -> Complex<f64> {
+        let unit = self.units_per_pixel();
+        self.rotate(Complex::new(
+            (x - self.width as f64 / 2.0) * unit,
+            (y - self.height as f64 / 2.0) * unit,
+        ))
+    }
+
+    /// Turns a distance measured along the canvas axes into one along the
+    /// plane's.
+    fn rotate(&self, canvas: Complex<f64>) -> Complex<f64> {
+        if self.rotation == 0.0 {
+            return canvas;
+        }
+        canvas * Complex::from_polar(1.0, self.rotation)
+    }
+
+    /// The center's real and imaginary parts to double-double precision.
+    pub fn precise_center(&self) -> (DoubleDouble, DoubleDouble) {
+        (
+            DoubleDouble::new(self.center.re, self.center_low.re),
+            DoubleDouble::new(self.center.im, self.center_low.im),
+        )
+    }
+
+    /// The canvas position of `point`, the inverse of [`Viewport::to_complex`].
+    pub fn to_canvas(&self, point: Complex<f64>) -> (f64, f64) {
+        let unit = self.units_per_pixel();
+        let mut offset = point - self.center;
+        if self.rotation != 0.0 {
+            offset *= Complex::from_polar(1.0, -self.rotation);
+        }
+        (
+            offset.re / unit + self.width as f64 / 2.0,
+            offset.im / unit + self.height as f64 / 2.0,
+        )
+    }
+
+    /// Magnifies the view by `factor` while keeping the point under `(x, y)`
+    /// fixed. Factors below one zoom out.
+    pub fn zoom_at(&mut self, x: f64, y: f64, factor: f64) {
+        let anchor = self.offset(x, y);
+        self.scale /= factor;
+        self.move_center(anchor - anchor / factor);
+    }
+
+    /// Moves the view so the content follows a drag of `(dx, dy)` pixels.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        let unit = self.units_per_pixel();
+        self.move_center(-self.rotate(Complex::new(dx * unit, dy * unit)));
+    }
+
+    /// Moves the center by `by` without losing the part an f64 would round off.
+    fn move_center(&mut self, by: Complex<f64>) {
+        let (re, im) = self.precise_center();
+        let (re, im) = (re + by.re, im + by.im);
+        self.center = Complex::new(re.hi, im.hi);
+        self.center_low = Complex::new(re.lo, im.lo);
+    }
+}